@@ -0,0 +1,103 @@
+use std::hint::black_box;
+use std::net::{IpAddr, Ipv4Addr};
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use subnet_utils::{CachingMatcher, ParsedSubnet, Subnet, SubnetSet, addr_in_any_subnet, addr_in_subnet, addr_in_subnet_cached};
+
+
+const SIZES: [usize; 3] = [10, 1_000, 100_000];
+
+/// Distinct /24s, none of which contain the address used for lookups.
+fn subnets(count: usize) -> Vec<String> {
+    (0..count).map(|i| format!("10.{}.{}.0/24", i / 256 % 256, i % 256)).collect()
+}
+
+/// A list of IPv6 /48s with a single IPv4 subnet at the end.
+fn mixed(count: usize) -> Vec<String> {
+    let mut subnets: Vec<String> = (0..count - 1).map(|i| format!("2001:db8:{:x}::/48", i % 65_536)).collect();
+    subnets.push("192.168.182.0/24".to_string());
+    subnets
+}
+
+/// Addresses in and around the subnets from [`subnets`], in no particular order.
+fn lookups(count: usize) -> Vec<IpAddr> {
+    (0..count as u32)
+        .map(|i| {
+            let n = i.wrapping_mul(2_654_435_761) % (count as u32 * 2);
+            IpAddr::V4(Ipv4Addr::new(10, (n / 256 % 256) as u8, (n % 256) as u8, (i % 256) as u8))
+        })
+        .collect()
+}
+
+fn miss() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1))
+}
+
+fn single(c: &mut Criterion) {
+    let addr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let mut group = c.benchmark_group("single");
+    group.bench_function("addr_in_subnet", |b| {
+        b.iter(|| addr_in_subnet(black_box(&addr), black_box("10.0.0.0/24")))
+    });
+    let mut cached = ParsedSubnet::new("10.0.0.0/24");
+    group.bench_function("addr_in_subnet_cached", |b| {
+        b.iter(|| addr_in_subnet_cached(black_box(&addr), &mut cached))
+    });
+    let subnet: Subnet = "10.0.0.0/24".parse().unwrap();
+    group.bench_function("Subnet::contains", |b| b.iter(|| black_box(&subnet).contains(black_box(&addr))));
+    group.finish();
+}
+
+fn lists(c: &mut Criterion) {
+    let addr = miss();
+    let mut group = c.benchmark_group("list");
+    group.sample_size(20);
+    for size in SIZES {
+        let owned = subnets(size);
+        let strs: Vec<&str> = owned.iter().map(String::as_str).collect();
+        group.bench_with_input(BenchmarkId::new("addr_in_any_subnet", size), &strs, |b, strs| {
+            b.iter(|| addr_in_any_subnet(black_box(&addr), black_box(strs)))
+        });
+        let set = SubnetSet::from_strs(&strs).unwrap();
+        group.bench_with_input(BenchmarkId::new("SubnetSet::contains", size), &set, |b, set| {
+            b.iter(|| set.contains(black_box(&addr)))
+        });
+        group.bench_with_input(BenchmarkId::new("SubnetSet::longest_match", size), &set, |b, set| {
+            b.iter(|| set.longest_match(black_box(&addr)))
+        });
+        let addrs = lookups(size);
+        group.bench_with_input(BenchmarkId::new("SubnetSet::longest_match_many", size), &addrs, |b, addrs| {
+            b.iter(|| set.longest_match_many(black_box(addrs)))
+        });
+        group.bench_with_input(BenchmarkId::new("SubnetSet::longest_match_loop", size), &addrs, |b, addrs| {
+            b.iter(|| black_box(addrs).iter().map(|addr| set.longest_match(addr)).collect::<Vec<_>>())
+        });
+        let mut matcher = CachingMatcher::new(set, 1024);
+        group.bench_function(BenchmarkId::new("CachingMatcher::lookup", size), |b| {
+            b.iter(|| matcher.lookup(black_box(&addr)))
+        });
+    }
+    group.finish();
+}
+
+/// IPv4 lookups against mostly IPv6 lists, where `addr_in_any_subnet` skips
+/// the IPv6 entries without fully parsing them, compared with parsing every entry.
+fn mixed_lists(c: &mut Criterion) {
+    let addr = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1));
+    let mut group = c.benchmark_group("mixed");
+    group.sample_size(20);
+    for size in SIZES {
+        let owned = mixed(size);
+        let strs: Vec<&str> = owned.iter().map(String::as_str).collect();
+        group.bench_with_input(BenchmarkId::new("addr_in_any_subnet", size), &strs, |b, strs| {
+            b.iter(|| addr_in_any_subnet(black_box(&addr), black_box(strs)))
+        });
+        group.bench_with_input(BenchmarkId::new("parse_every_subnet", size), &strs, |b, strs| {
+            b.iter(|| strs.iter().any(|s| addr_in_subnet(black_box(&addr), s).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single, lists, mixed_lists);
+criterion_main!(benches);
@@ -0,0 +1,671 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::iter::Fuse;
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{self, Subnet, check_prefix};
+
+
+/// Merge subnets into the minimal set of blocks covering the same addresses,
+/// sorted by family, network and prefix.
+pub(crate) fn aggregate(mut subnets: Vec<Subnet>) -> Vec<Subnet> {
+    subnets.sort();
+    subnets.dedup();
+    aggregate_sorted(subnets)
+}
+
+/// Aggregate subnets that are already sorted by family, network and prefix.
+pub(crate) fn aggregate_sorted(subnets: impl IntoIterator<Item = Subnet>) -> Vec<Subnet> {
+    aggregate_sorted_to(subnets, 0)
+}
+
+/// Like [`aggregate_sorted`], but never merges into a prefix shorter than `shortest`.
+fn aggregate_sorted_to(subnets: impl IntoIterator<Item = Subnet>, shortest: u8) -> Vec<Subnet> {
+    let mut stack: Vec<Subnet> = Vec::new();
+    for subnet in subnets {
+        push_merged(&mut stack, subnet, shortest);
+    }
+    stack
+}
+
+/// Push the next sorted `subnet` onto `stack`, skipping it if the top already
+/// contains it and merging sibling pairs on the top into prefixes no shorter
+/// than `shortest`.
+fn push_merged(stack: &mut Vec<Subnet>, subnet: Subnet, shortest: u8) {
+    if stack.last().is_some_and(|top| top.contains_subnet(&subnet)) {
+        return;
+    }
+    stack.push(subnet);
+    while stack.len() >= 2 {
+        let right = stack[stack.len() - 1];
+        let left = stack[stack.len() - 2];
+        match merge_siblings(&left, &right) {
+            Some(parent) if parent.prefix_len() >= shortest => {
+                stack.truncate(stack.len() - 2);
+                stack.push(parent);
+            }
+            _ => break,
+        }
+    }
+}
+
+/// The parent of `left` and `right` if they are the two halves of it.
+pub(crate) fn merge_siblings(left: &Subnet, right: &Subnet) -> Option<Subnet> {
+    if left.is_ipv6() != right.is_ipv6() || left.prefix_len() != right.prefix_len() || left.prefix_len() == 0 {
+        return None;
+    }
+    let size = left.size();
+    if left.first() & size != 0 || left.first() + size != right.first() {
+        return None;
+    }
+    Some(Subnet::from_bits(left.first(), left.prefix_len() - 1, left.is_ipv6()))
+}
+
+/// Like exact aggregation, but also merges blocks into supernets that cover
+/// addresses not in the input, as long as the added address space stays within
+/// `max_extra_ratio` of the input's size. The budget is applied to each family
+/// separately, and the cheapest merges are made first.
+///
+/// The result therefore matches addresses that none of the input subnets do.
+/// With a `max_extra_ratio` of `0.0` the result is the exact aggregation.
+///
+/// # Examples
+///
+/// ### Aggregate subnets allowing some over-coverage.
+///
+/// ```
+/// use subnet_utils::aggregate_lossy;
+///
+/// let subnets = vec!["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/24"];
+/// let res = aggregate_lossy(&subnets, 0.5).unwrap();
+/// assert_eq!(res, vec!["10.0.0.0/22"]);
+/// ```
+pub fn aggregate_lossy(subnets: &[&str], max_extra_ratio: f64) -> Result<Vec<String>, SubnetError> {
+    if max_extra_ratio.is_nan() || max_extra_ratio < 0.0 {
+        return Err(SubnetError::InvalidArgument(format!("invalid extra ratio {}", max_extra_ratio)));
+    }
+    let parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    let (v4, v6): (Vec<Subnet>, Vec<Subnet>) = aggregate(parsed).into_iter().partition(|s| !s.is_ipv6());
+    Ok(merge_lossy(v4, max_extra_ratio)
+        .into_iter()
+        .chain(merge_lossy(v6, max_extra_ratio))
+        .map(|s| s.to_string())
+        .collect())
+}
+
+/// Greedily merge aggregated blocks of one family while the extra space fits the budget.
+///
+/// Every merge candidate is the smallest supernet of two adjacent blocks, and
+/// its cost is the space in it that no block covers. Merging never changes
+/// which candidates exist except by absorbing them, and only lowers the cost
+/// of the merged block's ancestors, so the candidates are kept in a priority
+/// queue and only those ancestors are updated after each merge. That makes
+/// `n` blocks take O(n log n) time for each bit of the address width.
+fn merge_lossy(blocks: Vec<Subnet>, max_extra_ratio: f64) -> Vec<Subnet> {
+    let budget = blocks.iter().map(|s| s.size() as f64).sum::<f64>() * max_extra_ratio;
+    let mut costs: HashMap<Subnet, f64> = blocks
+        .windows(2)
+        .map(|pair| {
+            let supernet = common_supernet(&pair[0], &pair[1]);
+            let lo = blocks.partition_point(|s| s.first() < supernet.first());
+            let hi = blocks.partition_point(|s| s.first() <= supernet.last());
+            (supernet, supernet.size() as f64 - blocks[lo..hi].iter().map(|s| s.size() as f64).sum::<f64>())
+        })
+        .collect();
+    let mut blocks: BTreeMap<u128, Subnet> = blocks.into_iter().map(|s| (s.first(), s)).collect();
+    // Ordered by cost, then by position like a left-to-right scan of the pairs.
+    let mut queue: BinaryHeap<Reverse<(u64, u128, Subnet)>> =
+        costs.iter().map(|(supernet, cost)| Reverse((cost.max(0.0).to_bits(), midpoint(supernet), *supernet))).collect();
+    let mut extra = 0.0;
+    while let Some(Reverse((cost_bits, _, supernet))) = queue.pop() {
+        let cost = f64::from_bits(cost_bits);
+        let absorbed = blocks.range(..=supernet.first()).next_back().is_some_and(|(_, s)| s.contains_subnet(&supernet));
+        if absorbed || costs.get(&supernet).is_none_or(|c| c.max(0.0) != cost) {
+            continue;
+        }
+        if extra + cost > budget {
+            break;
+        }
+        extra += cost;
+        let inside: Vec<u128> = blocks.range(supernet.first()..=supernet.last()).map(|(first, _)| *first).collect();
+        for first in inside {
+            blocks.remove(&first);
+        }
+        let mut merged = supernet;
+        while merged.prefix_len() > 0 {
+            let sibling = Subnet::from_bits(merged.first() ^ merged.size(), merged.prefix_len(), merged.is_ipv6());
+            if blocks.get(&sibling.first()) != Some(&sibling) {
+                break;
+            }
+            blocks.remove(&sibling.first());
+            merged = Subnet::from_bits(merged.first(), merged.prefix_len() - 1, merged.is_ipv6());
+        }
+        blocks.insert(merged.first(), merged);
+        for prefix in (0..merged.prefix_len()).rev() {
+            let ancestor = Subnet::from_bits(merged.first(), prefix, merged.is_ipv6());
+            if let Some(c) = costs.get_mut(&ancestor) {
+                *c -= cost;
+                queue.push(Reverse((c.max(0.0).to_bits(), midpoint(&ancestor), ancestor)));
+            }
+        }
+    }
+    blocks.into_values().collect()
+}
+
+/// The first address of the upper half of `subnet`, which must not be a single address.
+fn midpoint(subnet: &Subnet) -> u128 {
+    subnet.first() | ((subnet.last() - subnet.first()) / 2 + 1)
+}
+
+/// The smallest subnet containing both `a` and `b`, which must be the same family.
+pub(crate) fn common_supernet(a: &Subnet, b: &Subnet) -> Subnet {
+    let width = a.max_prefix() as u32;
+    let common = ((a.first() ^ b.first()).leading_zeros() - (128 - width)) as u8;
+    let prefix = common.min(a.prefix_len()).min(b.prefix_len());
+    Subnet::from_bits(a.first(), prefix, a.is_ipv6())
+}
+
+/// The minimal aggregated union of `existing` and `additions`, which is the
+/// same as aggregating both lists together.
+///
+/// When `existing` is already sorted, as in a previously aggregated list, it
+/// is merged in a single pass instead of being sorted again.
+///
+/// # Examples
+///
+/// ### Merge new subnets into an aggregated list.
+///
+/// ```
+/// use subnet_utils::merge_into;
+///
+/// let existing = vec!["10.0.0.0/24", "10.0.2.0/24"];
+/// let res = merge_into(&existing, &["10.0.1.0/24", "10.0.3.0/24"]).unwrap();
+/// assert_eq!(res, vec!["10.0.0.0/22"]);
+/// ```
+pub fn merge_into(existing: &[&str], additions: &[&str]) -> Result<Vec<String>, SubnetError> {
+    let mut existing = existing.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    if !existing.is_sorted() {
+        existing.sort();
+    }
+    let additions = aggregate(additions.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?);
+
+    let mut merged = Vec::with_capacity(existing.len() + additions.len());
+    let (mut a, mut b) = (existing.into_iter().peekable(), additions.into_iter().peekable());
+    while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+        merged.push(if x <= y { a.next() } else { b.next() }.unwrap());
+    }
+    merged.extend(a);
+    merged.extend(b);
+    Ok(aggregate_sorted(merged).iter().map(|s| s.to_string()).collect())
+}
+
+/// The number of distinct IPv4 and IPv6 addresses covered by `subnets`,
+/// counting overlapping subnets only once. The IPv6 count saturates at
+/// `u128::MAX` when `::/0` is covered.
+///
+/// # Examples
+///
+/// ### Count the addresses in overlapping subnets.
+///
+/// ```
+/// use subnet_utils::distinct_address_count;
+///
+/// let res = distinct_address_count(&["10.0.0.0/24", "10.0.0.128/25", "2001:db8::/127"]).unwrap();
+/// assert_eq!(res, (256, 2));
+/// ```
+pub fn distinct_address_count(subnets: &[&str]) -> Result<(u128, u128), SubnetError> {
+    let parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    let (mut v4, mut v6) = (0u128, 0u128);
+    for subnet in aggregate(parsed) {
+        if subnet.is_ipv6() {
+            v6 = v6.saturating_add(subnet.size());
+        } else {
+            v4 += subnet.size();
+        }
+    }
+    Ok((v4, v6))
+}
+
+/// Collapses host addresses into the minimal CIDR blocks covering exactly those
+/// addresses, without creating any block shorter than `max_prefix`. With a
+/// `max_prefix` of `24`, hosts that don't fill a `/24` stay as smaller blocks.
+///
+/// `max_prefix` must be valid for every address family present.
+///
+/// # Examples
+///
+/// ### Collapse contiguous hosts into subnets.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::collapse_hosts;
+///
+/// let addrs: Vec<IpAddr> = (0..=5).map(|i| IpAddr::V4(Ipv4Addr::new(10, 0, 0, i))).collect();
+/// let res = collapse_hosts(&addrs, 24).unwrap();
+/// assert_eq!(res, vec!["10.0.0.0/30", "10.0.0.4/31"]);
+/// ```
+pub fn collapse_hosts(addrs: &[IpAddr], max_prefix: u8) -> Result<Vec<String>, SubnetError> {
+    let mut hosts = Vec::with_capacity(addrs.len());
+    for addr in addrs.iter() {
+        check_prefix(max_prefix, addr.is_ipv6())?;
+        hosts.push(Subnet::from_parts(*addr, subnet::max_prefix(addr.is_ipv6()))?);
+    }
+    hosts.sort();
+    hosts.dedup();
+    Ok(aggregate_sorted_to(hosts, max_prefix).iter().map(|s| s.to_string()).collect())
+}
+
+
+/// The minimal set of blocks covering every address of the subnet's family
+/// except those in `subnet`, in address order. The complement of `0.0.0.0/0`
+/// or `::/0` is empty.
+///
+/// # Examples
+///
+/// ### Get the rest of the address space around a subnet.
+///
+/// ```
+/// use subnet_utils::complement;
+///
+/// let res = complement("128.0.0.0/2").unwrap();
+/// assert_eq!(res, vec!["0.0.0.0/1", "192.0.0.0/2"]);
+/// ```
+pub fn complement(subnet: &str) -> Result<Vec<String>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let (max, v6) = (subnet.max_prefix(), subnet.is_ipv6());
+    let mut siblings: Vec<Subnet> = (1..=subnet.prefix_len())
+        .map(|prefix| Subnet::from_bits(subnet.first() ^ (1 << (max - prefix)), prefix, v6))
+        .collect();
+    siblings.sort();
+    Ok(siblings.iter().map(|s| s.to_string()).collect())
+}
+
+
+/// Removes duplicates and any block whose addresses are all covered by more
+/// specific blocks in the list, keeping the most granular representation. This
+/// is the opposite preference to aggregation, which keeps the widest blocks.
+///
+/// A broader block that is only partly covered by more specific ones is kept,
+/// since dropping it would lose addresses. The result is sorted by family,
+/// network and prefix.
+///
+/// # Examples
+///
+/// ### Drop a block covered by more specific ones.
+///
+/// ```
+/// use subnet_utils::dedupe_keep_specific;
+///
+/// let subnets = vec!["10.0.0.0/24", "10.0.0.0/25", "10.0.0.128/25", "10.0.1.0/24", "10.0.1.0/25"];
+/// let res = dedupe_keep_specific(&subnets).unwrap();
+/// assert_eq!(res, vec!["10.0.0.0/25", "10.0.0.128/25", "10.0.1.0/24", "10.0.1.0/25"]);
+/// ```
+pub fn dedupe_keep_specific(subnets: &[&str]) -> Result<Vec<String>, SubnetError> {
+    let mut parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    parsed.sort();
+    parsed.dedup();
+    let mut res = Vec::with_capacity(parsed.len());
+    for (i, subnet) in parsed.iter().enumerate() {
+        // Blocks inside `subnet` sort directly after it.
+        let inside: Vec<Subnet> = parsed[i + 1..].iter().take_while(|s| subnet.contains_subnet(s)).copied().collect();
+        if aggregate_sorted(inside) != [*subnet] {
+            res.push(subnet.to_string());
+        }
+    }
+    Ok(res)
+}
+
+/// Check if `subnets` is already minimal, i.e. no two entries overlap and no
+/// two are sibling halves that could merge, so aggregating it would return
+/// the same blocks in sorted order.
+///
+/// This sorts the list and compares neighbours instead of aggregating it.
+///
+/// # Examples
+///
+/// ### Check if a list needs aggregating.
+///
+/// ```
+/// use subnet_utils::is_aggregated;
+///
+/// assert!(is_aggregated(&["10.0.2.0/24", "10.0.0.0/24"]).unwrap());
+/// assert!(!is_aggregated(&["10.0.1.0/24", "10.0.0.0/24"]).unwrap());
+/// ```
+pub fn is_aggregated(subnets: &[&str]) -> Result<bool, SubnetError> {
+    let mut parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    parsed.sort();
+    Ok(parsed.windows(2).all(|pair| {
+        let (left, right) = (&pair[0], &pair[1]);
+        let overlaps = left.is_ipv6() == right.is_ipv6() && right.first() <= left.last();
+        !overlaps && merge_siblings(left, right).is_none()
+    }))
+}
+
+/// The address space `new` gains and loses relative to `old`, as the minimal
+/// blocks of `(gained, lost)`: the addresses only in `new` and those only in
+/// `old`. Unlike comparing the lists entry by entry, this is a true set
+/// difference, so differently split lists covering the same addresses have no
+/// difference.
+///
+/// # Examples
+///
+/// ### Find which addresses a change grants and revokes.
+///
+/// ```
+/// use subnet_utils::space_diff;
+///
+/// let (gained, lost) = space_diff(&["10.0.0.0/24"], &["10.0.0.0/25", "10.0.1.0/24"]).unwrap();
+/// assert_eq!(gained, vec!["10.0.1.0/24"]);
+/// assert_eq!(lost, vec!["10.0.0.128/25"]);
+/// ```
+pub fn space_diff(old: &[&str], new: &[&str]) -> Result<(Vec<String>, Vec<String>), SubnetError> {
+    let old = aggregate(old.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?);
+    let new = aggregate(new.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?);
+    let difference = |from: &[Subnet], remove: &[Subnet]| {
+        let mut res = Vec::new();
+        for subnet in from.iter() {
+            subtract(*subnet, remove, &mut res);
+        }
+        aggregate_sorted(res).iter().map(|s| s.to_string()).collect()
+    };
+    Ok((difference(&new, &old), difference(&old, &new)))
+}
+
+/// Push the blocks of `subnet` not covered by the sorted, aggregated `remove`
+/// onto `res`, in address order, splitting `subnet` in half wherever only part
+/// of it is covered.
+fn subtract(subnet: Subnet, remove: &[Subnet], res: &mut Vec<Subnet>) {
+    let key = (subnet.is_ipv6(), subnet.first());
+    let index = remove.partition_point(|s| (s.is_ipv6(), s.last()) < key);
+    match remove.get(index) {
+        Some(other) if other.is_ipv6() == subnet.is_ipv6() && other.first() <= subnet.last() => {
+            if !other.contains_subnet(&subnet) {
+                for half in subnet.subnets(subnet.prefix_len() + 1) {
+                    subtract(half, &remove[index..], res);
+                }
+            }
+        }
+        _ => res.push(subnet),
+    }
+}
+
+/// Lazily aggregate a stream of subnets, yielding the same blocks as exact
+/// aggregation without collecting the input first.
+///
+/// The input must already be sorted by family, network and then prefix, which
+/// is the order of [`Subnet`]'s `Ord`, for example a pre-sorted file. Unsorted
+/// input still yields valid blocks covering the same addresses, but they may
+/// overlap or not be minimal. Only blocks that can still merge with later
+/// input are held back, at most one per prefix length.
+///
+/// # Examples
+///
+/// ### Aggregate a sorted stream of subnets.
+///
+/// ```
+/// use subnet_utils::{Subnet, aggregate_sorted_stream};
+///
+/// let input = ["10.0.0.0/25", "10.0.0.128/25", "10.0.1.0/24", "10.0.1.5/32", "10.0.3.0/24"];
+/// let res: Vec<String> = aggregate_sorted_stream(input.iter().map(|s| s.parse::<Subnet>().unwrap()))
+///     .map(|subnet| subnet.to_string())
+///     .collect();
+/// assert_eq!(res, vec!["10.0.0.0/23", "10.0.3.0/24"]);
+/// ```
+pub fn aggregate_sorted_stream<I: Iterator<Item = Subnet>>(subnets: I) -> impl Iterator<Item = Subnet> {
+    AggregateStream { subnets: subnets.fuse(), stack: Vec::new() }
+}
+
+/// Iterator behind [`aggregate_sorted_stream`]. The stack holds ascending,
+/// disjoint blocks, and the bottom one is yielded once nothing can merge into it.
+struct AggregateStream<I> {
+    subnets: Fuse<I>,
+    stack: Vec<Subnet>,
+}
+
+impl<I: Iterator<Item = Subnet>> Iterator for AggregateStream<I> {
+    type Item = Subnet;
+
+    fn next(&mut self) -> Option<Subnet> {
+        loop {
+            if self.stack.len() >= 2 && is_final(&self.stack[0], &self.stack[1]) {
+                return Some(self.stack.remove(0));
+            }
+            match self.subnets.next() {
+                Some(subnet) => push_merged(&mut self.stack, subnet, 0),
+                None if self.stack.is_empty() => return None,
+                None => return Some(self.stack.remove(0)),
+            }
+        }
+    }
+}
+
+/// Check if `block` can no longer merge, given the block after it in the
+/// stack. It could only grow by merging with its upper sibling, which needs
+/// `block` to be a lower half with `next` filling that sibling from the start.
+fn is_final(block: &Subnet, next: &Subnet) -> bool {
+    block.prefix_len() == 0
+        || block.first() & block.size() != 0
+        || block.is_ipv6() != next.is_ipv6()
+        || block.last().checked_add(1) != Some(next.first())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use crate::SubnetSet;
+
+    fn parse(subnets: &[&str]) -> Vec<Subnet> {
+        subnets.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let subnets = parse(&["10.0.1.0/24", "10.0.0.0/24", "10.0.0.128/25", "2001:db8::/33", "2001:db8:8000::/33"]);
+        let res: Vec<String> = aggregate(subnets).iter().map(|s| s.to_string()).collect();
+        assert_eq!(res, vec!["10.0.0.0/23", "2001:db8::/32"]);
+    }
+
+    #[test]
+    fn test_aggregate_not_siblings() {
+        let subnets = parse(&["10.0.1.0/24", "10.0.2.0/24"]);
+        let res: Vec<String> = aggregate(subnets).iter().map(|s| s.to_string()).collect();
+        assert_eq!(res, vec!["10.0.1.0/24", "10.0.2.0/24"]);
+    }
+
+    #[test]
+    fn test_aggregate_lossy() {
+        let subnets = vec!["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/24", "10.0.8.0/24"];
+        let res = aggregate_lossy(&subnets, 0.5).unwrap();
+        assert_eq!(res, vec!["10.0.0.0/22", "10.0.8.0/24"]);
+        let res = aggregate_lossy(&subnets, 3.0).unwrap();
+        assert_eq!(res, vec!["10.0.0.0/20"]);
+    }
+
+    #[test]
+    fn test_aggregate_lossy_exact() {
+        let subnets = vec!["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/24", "2001:db8::/33", "2001:db8:8000::/33"];
+        let res = aggregate_lossy(&subnets, 0.0).unwrap();
+        let exact: Vec<String> = aggregate(subnets.iter().map(|s| s.parse().unwrap()).collect())
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(res, exact);
+        assert!(aggregate_lossy(&subnets, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_lossy_large() {
+        let owned: Vec<String> = (0..100_000u32).map(|i| format!("{}/32", Ipv4Addr::from(0x0a00_0000 + i * 3))).collect();
+        let subnets: Vec<&str> = owned.iter().map(String::as_str).collect();
+        let res = aggregate_lossy(&subnets, 1.0).unwrap();
+        let set: SubnetSet = res.iter().map(|s| s.parse::<Subnet>().unwrap()).collect();
+        assert!(set.len() < subnets.len());
+        assert!(set.iter().map(|s| s.size()).sum::<u128>() <= 200_000);
+        assert!((0..100_000u32).all(|i| set.contains(&IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + i * 3)))));
+    }
+
+    #[test]
+    fn test_merge_into() {
+        let existing = vec!["10.0.0.0/24", "10.0.2.0/23", "192.168.0.0/16", "2001:db8::/33"];
+        let additions = vec!["2001:db8:8000::/33", "10.0.1.0/24", "10.0.2.128/25", "172.16.0.0/12"];
+        let res = merge_into(&existing, &additions).unwrap();
+        assert_eq!(res, vec!["10.0.0.0/22", "172.16.0.0/12", "192.168.0.0/16", "2001:db8::/32"]);
+    }
+
+    #[test]
+    fn test_merge_into_equals_aggregate() {
+        let cases: Vec<(Vec<&str>, Vec<&str>)> = vec![
+            (vec!["10.0.0.0/24", "10.0.1.0/24"], vec!["10.0.2.0/24", "10.0.3.0/25", "10.0.3.128/25"]),
+            (vec!["10.0.3.0/24", "10.0.0.0/8"], vec!["10.1.0.0/16", "11.0.0.0/8"]),
+            (vec![], vec!["::/1", "8000::/1", "0.0.0.0/1"]),
+            (vec!["192.168.182.0/24"], vec![]),
+        ];
+        for (existing, additions) in cases {
+            let all: Vec<&str> = existing.iter().chain(additions.iter()).copied().collect();
+            let expected: Vec<String> = aggregate(parse(&all)).iter().map(|s| s.to_string()).collect();
+            assert_eq!(merge_into(&existing, &additions).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_distinct_address_count() {
+        let res = distinct_address_count(&["10.0.0.0/24", "10.0.0.128/25", "10.0.1.0/24", "2001:db8::/64"]).unwrap();
+        assert_eq!(res, (512, 1 << 64));
+        assert_eq!(distinct_address_count(&[]).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_distinct_address_count_whole_space() {
+        let res = distinct_address_count(&["0.0.0.0/0", "10.0.0.0/8", "::/0"]).unwrap();
+        assert_eq!(res, (1 << 32, u128::MAX));
+    }
+
+    #[test]
+    fn test_collapse_hosts() {
+        let mut addrs: Vec<IpAddr> = (0..=255).map(|i| format!("10.0.1.{}", i).parse().unwrap()).collect();
+        addrs.push("10.0.2.7".parse().unwrap());
+        addrs.push("2001:db8::1".parse().unwrap());
+        addrs.push("2001:db8::".parse().unwrap());
+        addrs.push("10.0.2.7".parse().unwrap());
+        let res = collapse_hosts(&addrs, 24).unwrap();
+        assert_eq!(res, vec!["10.0.1.0/24", "10.0.2.7/32", "2001:db8::/127"]);
+    }
+
+    #[test]
+    fn test_collapse_hosts_limit() {
+        let addrs: Vec<IpAddr> = (0..=255).map(|i| format!("10.0.1.{}", i).parse().unwrap()).collect();
+        let res = collapse_hosts(&addrs, 25).unwrap();
+        assert_eq!(res, vec!["10.0.1.0/25", "10.0.1.128/25"]);
+        let res = collapse_hosts(&addrs, 33);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+    }
+
+    #[test]
+    fn test_complement() {
+        let res = complement("10.0.0.0/8").unwrap();
+        let expected = vec!["0.0.0.0/5", "8.0.0.0/7", "11.0.0.0/8", "12.0.0.0/6", "16.0.0.0/4", "32.0.0.0/3", "64.0.0.0/2", "128.0.0.0/1"];
+        assert_eq!(res, expected);
+        let res = complement("255.255.255.255/32").unwrap();
+        assert_eq!(res.len(), 32);
+        assert_eq!(res.last().map(String::as_str), Some("255.255.255.254/32"));
+        let res = complement("::/1").unwrap();
+        assert_eq!(res, vec!["8000::/1"]);
+    }
+
+    #[test]
+    fn test_complement_whole_space() {
+        assert!(complement("0.0.0.0/0").unwrap().is_empty());
+        assert!(complement("::/0").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dedupe_keep_specific() {
+        let subnets = vec!["10.0.0.0/23", "10.0.0.0/24", "10.0.1.0/25", "10.0.1.128/25", "10.0.0.0/24", "2001:db8::/32"];
+        let res = dedupe_keep_specific(&subnets).unwrap();
+        assert_eq!(res, vec!["10.0.0.0/24", "10.0.1.0/25", "10.0.1.128/25", "2001:db8::/32"]);
+    }
+
+    #[test]
+    fn test_dedupe_keep_specific_partial() {
+        let subnets = vec!["10.0.0.0/24", "10.0.0.0/26", "10.0.0.128/25"];
+        let res = dedupe_keep_specific(&subnets).unwrap();
+        assert_eq!(res, vec!["10.0.0.0/24", "10.0.0.0/26", "10.0.0.128/25"]);
+        assert!(dedupe_keep_specific(&["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_sorted_stream() {
+        let input = ["0.0.0.0/1", "128.0.0.0/1", "::/1", "::1/128", "8000::/1"];
+        let subnets: Vec<Subnet> = input.iter().map(|s| s.parse().unwrap()).collect();
+        let res: Vec<String> = aggregate_sorted_stream(subnets.into_iter()).map(|s| s.to_string()).collect();
+        assert_eq!(res, vec!["0.0.0.0/0", "::/0"]);
+        assert_eq!(aggregate_sorted_stream(std::iter::empty()).count(), 0);
+    }
+
+    #[test]
+    fn test_aggregate_sorted_stream_matches_aggregate() {
+        let subnets: Vec<Subnet> = (0..500u32)
+            .map(|i| Subnet::from_bits((i * 7919 % 1024) as u128 * 4, 28 + (i % 5) as u8, i % 3 == 0))
+            .collect();
+        let expected = aggregate(subnets.clone());
+        let mut sorted = subnets;
+        sorted.sort();
+        let mut stream = aggregate_sorted_stream(sorted.into_iter());
+        let res: Vec<Subnet> = stream.by_ref().collect();
+        assert_eq!(res, expected);
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn test_aggregate_sorted_stream_is_lazy() {
+        let mut stream = aggregate_sorted_stream((0..).map(|i: u128| Subnet::from_bits(i << 9, 24, false)));
+        assert_eq!(stream.next().unwrap().to_string(), "0.0.0.0/24");
+        assert_eq!(stream.next().unwrap().to_string(), "0.0.2.0/24");
+    }
+
+    #[test]
+    fn test_is_aggregated() {
+        assert!(is_aggregated(&[]).unwrap());
+        assert!(is_aggregated(&["0.0.0.0/0", "::/0"]).unwrap());
+        assert!(is_aggregated(&["10.0.1.0/24", "10.0.2.0/24"]).unwrap());
+        assert!(is_aggregated(&["255.255.255.255/32", "::/128"]).unwrap());
+        assert!(is_aggregated(&["10.0.0.0/22", "10.0.4.0/23", "10.0.6.0/24"]).unwrap());
+    }
+
+    #[test]
+    fn test_not_is_aggregated() {
+        assert!(!is_aggregated(&["10.0.0.0/24", "10.0.0.0/24"]).unwrap());
+        assert!(!is_aggregated(&["10.0.0.0/16", "10.0.200.0/24"]).unwrap());
+        assert!(!is_aggregated(&["2001:db8::/33", "2001:db8:8000::/33"]).unwrap());
+        assert!(is_aggregated(&["10.0.0.0/8", "zoop"]).is_err());
+        for input in [vec!["10.0.0.0/24", "10.0.0.128/25", "10.0.1.0/24"], vec!["10.0.0.0/25", "10.0.0.128/25"]] {
+            let expected: Vec<String> = aggregate(input.iter().map(|s| s.parse().unwrap()).collect()).iter().map(|s| s.to_string()).collect();
+            assert_eq!(is_aggregated(&input).unwrap(), expected.len() == input.len());
+        }
+    }
+
+    #[test]
+    fn test_space_diff() {
+        let (gained, lost) = space_diff(&["10.0.0.0/23"], &["10.0.0.0/24", "10.0.1.0/24"]).unwrap();
+        assert!(gained.is_empty() && lost.is_empty());
+        let (gained, lost) = space_diff(&["10.0.0.0/8", "2001:db8::/32"], &["10.0.0.0/8", "10.1.2.3/32"]).unwrap();
+        assert!(gained.is_empty());
+        assert_eq!(lost, vec!["2001:db8::/32"]);
+        let (gained, lost) = space_diff(&[], &["0.0.0.0/0"]).unwrap();
+        assert_eq!((gained, lost), (vec!["0.0.0.0/0".to_string()], vec![]));
+    }
+
+    #[test]
+    fn test_space_diff_partial() {
+        let (gained, lost) = space_diff(&["10.0.0.0/8"], &["10.0.0.0/8", "11.0.0.0/8"]).unwrap();
+        assert_eq!((gained, lost), (vec!["11.0.0.0/8".to_string()], vec![]));
+        let (gained, lost) = space_diff(&["10.0.0.0/24"], &["10.0.0.0/25", "10.0.0.192/26", "10.0.0.129/32"]).unwrap();
+        assert!(gained.is_empty());
+        let expected = vec!["10.0.0.128/32", "10.0.0.130/31", "10.0.0.132/30", "10.0.0.136/29", "10.0.0.144/28", "10.0.0.160/27"];
+        assert_eq!(lost, expected);
+        assert!(space_diff(&["zoop"], &[]).is_err());
+    }
+}
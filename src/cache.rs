@@ -0,0 +1,114 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+
+use crate::set::SubnetSet;
+
+
+/// A [`SubnetSet`] with a least-recently-used cache of lookup results, for
+/// workloads that look up the same addresses over and over, e.g. client IPs
+/// in a proxy.
+///
+/// Lookups take `&mut self` to update the cache, so a matcher is meant for
+/// use from a single thread. Wrap it in a `Mutex` to share it, or give each
+/// thread its own.
+///
+/// # Examples
+///
+/// ### Look up an address through the cache.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{CachingMatcher, SubnetSet};
+///
+/// let set = SubnetSet::from_strs(&["10.0.0.0/8", "10.1.0.0/16"]).unwrap();
+/// let mut matcher = CachingMatcher::new(set, 1024);
+/// let addr = IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3));
+/// assert_eq!(matcher.lookup(&addr), Some(1));
+/// assert_eq!(matcher.lookup(&addr), Some(1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CachingMatcher {
+    set: SubnetSet,
+    capacity: usize,
+    /// The cached result of each address and when it was last used.
+    entries: HashMap<IpAddr, (Option<usize>, u64)>,
+    /// The cached addresses by when they were last used, oldest first.
+    recency: BTreeMap<u64, IpAddr>,
+    tick: u64,
+}
+
+impl CachingMatcher {
+    /// Wrap `set`, caching the results of up to `capacity` addresses. A
+    /// `capacity` of `0` disables the cache.
+    pub fn new(set: SubnetSet, capacity: usize) -> Self {
+        CachingMatcher { set, capacity, entries: HashMap::new(), recency: BTreeMap::new(), tick: 0 }
+    }
+
+    /// Like [`SubnetSet::longest_match`], but answered from the cache when
+    /// `addr` was looked up recently. On a miss, the least recently used
+    /// address is evicted if the cache is full.
+    pub fn lookup(&mut self, addr: &IpAddr) -> Option<usize> {
+        self.tick += 1;
+        if let Some((res, used)) = self.entries.get_mut(addr) {
+            self.recency.remove(used);
+            self.recency.insert(self.tick, *addr);
+            *used = self.tick;
+            return *res;
+        }
+        let res = self.set.longest_match(addr);
+        if self.capacity == 0 {
+            return res;
+        }
+        if self.entries.len() >= self.capacity
+            && let Some((_, oldest)) = self.recency.pop_first()
+        {
+            self.entries.remove(&oldest);
+        }
+        self.entries.insert(*addr, (res, self.tick));
+        self.recency.insert(self.tick, *addr);
+        res
+    }
+
+    /// The wrapped set.
+    pub fn set(&self) -> &SubnetSet {
+        &self.set
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caching_matcher_lookup() {
+        let set = SubnetSet::from_strs(&["10.0.0.0/8", "10.1.0.0/16", "2001:db8::/32"]).unwrap();
+        let mut matcher = CachingMatcher::new(set.clone(), 2);
+        for addr in ["10.1.0.1", "10.2.0.1", "2001:db8::1", "192.168.0.1", "10.1.0.1", "2001:db8::1"] {
+            let addr: IpAddr = addr.parse().unwrap();
+            assert_eq!(matcher.lookup(&addr), set.longest_match(&addr));
+            assert_eq!(matcher.lookup(&addr), set.longest_match(&addr));
+        }
+        assert_eq!(matcher.entries.len(), 2);
+        assert_eq!(matcher.set(), &set);
+    }
+
+    #[test]
+    fn test_caching_matcher_evicts_least_recently_used() {
+        let set = SubnetSet::from_strs(&["10.0.0.0/8"]).unwrap();
+        let mut matcher = CachingMatcher::new(set.clone(), 2);
+        let addrs: Vec<IpAddr> = ["10.0.0.1", "10.0.0.2", "10.0.0.3"].iter().map(|s| s.parse().unwrap()).collect();
+        matcher.lookup(&addrs[0]);
+        matcher.lookup(&addrs[1]);
+        matcher.lookup(&addrs[0]);
+        matcher.lookup(&addrs[2]);
+        assert!(matcher.entries.contains_key(&addrs[0]));
+        assert!(!matcher.entries.contains_key(&addrs[1]));
+        assert!(matcher.entries.contains_key(&addrs[2]));
+        assert_eq!(matcher.recency.len(), 2);
+
+        let mut matcher = CachingMatcher::new(set, 0);
+        assert_eq!(matcher.lookup(&addrs[0]), Some(0));
+        assert!(matcher.entries.is_empty());
+    }
+}
@@ -0,0 +1,853 @@
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, addr_to_bits, bits_to_addr, check_prefix, host_mask, max_prefix};
+
+
+/// The same-size subnet immediately after `subnet`, or `None` if it would
+/// overflow the address space.
+///
+/// # Examples
+///
+/// ### Get the next subnet of the same size.
+///
+/// ```
+/// use subnet_utils::next_subnet;
+///
+/// let res = next_subnet("10.0.0.0/24").unwrap();
+/// assert_eq!(res.as_deref(), Some("10.0.1.0/24"));
+/// ```
+pub fn next_subnet(subnet: &str) -> Result<Option<String>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if subnet.last() == host_mask(0, subnet.is_ipv6()) {
+        return Ok(None);
+    }
+    Ok(Some(Subnet::from_bits(subnet.last() + 1, subnet.prefix_len(), subnet.is_ipv6()).to_string()))
+}
+
+/// The same-size subnet immediately before `subnet`, or `None` if it would
+/// underflow the address space.
+///
+/// # Examples
+///
+/// ### Get the previous subnet of the same size.
+///
+/// ```
+/// use subnet_utils::prev_subnet;
+///
+/// let res = prev_subnet("10.0.0.0/24").unwrap();
+/// assert_eq!(res.as_deref(), Some("9.255.255.0/24"));
+/// ```
+pub fn prev_subnet(subnet: &str) -> Result<Option<String>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if subnet.first() == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Subnet::from_bits(subnet.first() - 1, subnet.prefix_len(), subnet.is_ipv6()).to_string()))
+}
+
+/// The broadcast address of the subnet `addr`/`prefix`. For IPv6 this is the
+/// last address of the subnet.
+///
+/// # Examples
+///
+/// ### Get the broadcast address for an interface address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::broadcast_for;
+///
+/// let res = broadcast_for(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 37)), 24).unwrap();
+/// assert_eq!(res, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 255)));
+/// ```
+pub fn broadcast_for(addr: &IpAddr, prefix: u8) -> Result<IpAddr, SubnetError> {
+    let subnet = Subnet::from_parts(*addr, prefix)?;
+    Ok(bits_to_addr(subnet.last(), subnet.is_ipv6()))
+}
+
+/// The absolute difference between two addresses of the same family.
+///
+/// # Examples
+///
+/// ### Get the distance between two addresses.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::address_distance;
+///
+/// let a = IpAddr::V4(Ipv4Addr::new(10, 0, 1, 0));
+/// let b = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 255));
+/// let res = address_distance(&a, &b).unwrap();
+/// assert_eq!(res, 1);
+/// ```
+pub fn address_distance(a: &IpAddr, b: &IpAddr) -> Result<u128, SubnetError> {
+    if a.is_ipv6() != b.is_ipv6() {
+        return Err(SubnetError::FamilyMismatch);
+    }
+    Ok(addr_to_bits(a).abs_diff(addr_to_bits(b)))
+}
+
+/// Checks if `a` and `b` are in the same subnet of length `prefix`. A `/32` or
+/// `/128` only holds a single host, so it never counts as shared. Addresses of
+/// different families are never in the same broadcast domain, but `prefix`
+/// must still be valid for `a`'s family.
+///
+/// # Examples
+///
+/// ### Check if two addresses share a broadcast domain.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::same_broadcast_domain;
+///
+/// let a = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1));
+/// let b = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 254));
+/// let res = same_broadcast_domain(&a, &b, 24).unwrap();
+/// assert!(res);
+/// ```
+pub fn same_broadcast_domain(a: &IpAddr, b: &IpAddr, prefix: u8) -> Result<bool, SubnetError> {
+    check_prefix(prefix, a.is_ipv6())?;
+    if a.is_ipv6() != b.is_ipv6() {
+        return Ok(false);
+    }
+    let subnet = Subnet::from_parts(*a, prefix)?;
+    Ok(prefix < subnet.max_prefix() && subnet.contains(b))
+}
+
+/// Zeroes the host bits of `addr` for a prefix of length `prefix`, returning the
+/// network address.
+///
+/// # Examples
+///
+/// ### Mask an address to its network address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::mask_addr;
+///
+/// let res = mask_addr(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 37)), 24).unwrap();
+/// assert_eq!(res, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)));
+/// ```
+pub fn mask_addr(addr: &IpAddr, prefix: u8) -> Result<IpAddr, SubnetError> {
+    Ok(Subnet::from_parts(*addr, prefix)?.network())
+}
+
+/// Checks if `addr` is the last address of `subnet`: the broadcast address for
+/// IPv4, and the highest address for IPv6. Addresses of the other family never match.
+///
+/// # Examples
+///
+/// ### Check if an address is the last address of a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::is_last_address;
+///
+/// let res = is_last_address(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 255)), "192.168.182.0/24").unwrap();
+/// assert!(res);
+/// ```
+pub fn is_last_address(addr: &IpAddr, subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(subnet.contains(addr) && addr_to_bits(addr) == subnet.last())
+}
+
+
+/// Check if `subnet` is a point-to-point link block, a /31 for IPv4
+/// ([RFC 3021](https://www.rfc-editor.org/rfc/rfc3021)) or a /127 for IPv6
+/// ([RFC 6164](https://www.rfc-editor.org/rfc/rfc6164)).
+///
+/// # Examples
+///
+/// ### Check if a subnet is a point-to-point link.
+///
+/// ```
+/// use subnet_utils::is_point_to_point;
+///
+/// let res = is_point_to_point("10.0.0.0/31").unwrap();
+/// assert!(res);
+/// ```
+pub fn is_point_to_point(subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(subnet.prefix_len() + 1 == subnet.max_prefix())
+}
+
+
+/// The prefix length of a subnet with `host_bits` host bits, the family's
+/// address width minus `host_bits`.
+///
+/// # Examples
+///
+/// ### Convert a host bit count to a prefix length.
+///
+/// ```
+/// use subnet_utils::host_bits_to_prefix;
+///
+/// let res = host_bits_to_prefix(8, false).unwrap();
+/// assert_eq!(res, 24);
+/// ```
+pub fn host_bits_to_prefix(host_bits: u8, v6: bool) -> Result<u8, SubnetError> {
+    let max = max_prefix(v6);
+    if host_bits > max {
+        return Err(SubnetError::InvalidArgument(format!("{} host bits exceeds the address width of {}", host_bits, max)));
+    }
+    Ok(max - host_bits)
+}
+
+/// The number of host bits in a subnet with the prefix length `prefix`.
+///
+/// # Examples
+///
+/// ### Convert a prefix length to a host bit count.
+///
+/// ```
+/// use subnet_utils::prefix_to_host_bits;
+///
+/// let res = prefix_to_host_bits(56, true).unwrap();
+/// assert_eq!(res, 72);
+/// ```
+pub fn prefix_to_host_bits(prefix: u8, v6: bool) -> Result<u8, SubnetError> {
+    check_prefix(prefix, v6)?;
+    Ok(max_prefix(v6) - prefix)
+}
+
+
+/// Where a subnet's gateway conventionally sits, see [`conventional_gateway`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GatewayPosition {
+    /// The first usable address, e.g. `.1` in an IPv4 /24.
+    First,
+    /// The last usable address, e.g. `.254` in an IPv4 /24.
+    Last,
+}
+
+/// The gateway address of `subnet` under the given convention.
+///
+/// Point-to-point (/31, /127) and single address (/32, /128) subnets have no
+/// conventional gateway and return an [`InvalidArgument`](SubnetError::InvalidArgument)
+/// error. For IPv6 the last usable address is the last address of the subnet.
+///
+/// # Examples
+///
+/// ### Get the conventional gateways of a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{GatewayPosition, conventional_gateway};
+///
+/// let res = conventional_gateway("192.168.182.0/24", GatewayPosition::First).unwrap();
+/// assert_eq!(res, IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)));
+/// let res = conventional_gateway("192.168.182.0/24", GatewayPosition::Last).unwrap();
+/// assert_eq!(res, IpAddr::V4(Ipv4Addr::new(192, 168, 182, 254)));
+/// ```
+pub fn conventional_gateway(subnet: &str, position: GatewayPosition) -> Result<IpAddr, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if subnet.max_prefix() - subnet.prefix_len() <= 1 {
+        return Err(SubnetError::InvalidArgument(format!("no conventional gateway in {}", subnet)));
+    }
+    let (first, last) = subnet.usable();
+    let gateway = match position {
+        GatewayPosition::First => first,
+        GatewayPosition::Last => last,
+    };
+    Ok(bits_to_addr(gateway, subnet.is_ipv6()))
+}
+
+
+/// The network mask of `subnet` bit by bit from the most significant, `true`
+/// for network bits. The result has 32 entries for IPv4 and 128 for IPv6.
+///
+/// The mask is always contiguous; subnets given with a non-contiguous mask
+/// such as `255.0.255.0` fail to parse.
+///
+/// # Examples
+///
+/// ### Get the bits of a subnet mask.
+///
+/// ```
+/// use subnet_utils::mask_bits;
+///
+/// let res = mask_bits("10.0.0.0/255.255.255.0").unwrap();
+/// assert_eq!(res.len(), 32);
+/// assert_eq!(res.iter().filter(|b| **b).count(), 24);
+/// ```
+pub fn mask_bits(subnet: &str) -> Result<Vec<bool>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok((0..subnet.max_prefix()).map(|bit| bit < subnet.prefix_len()).collect())
+}
+
+
+/// The number of addresses in a block of length `prefix`, the "magic number"
+/// step between successive subnets of that size. A `/0` IPv6 block saturates
+/// at `u128::MAX`.
+///
+/// # Examples
+///
+/// ### Get the block size of a prefix.
+///
+/// ```
+/// use subnet_utils::block_size;
+///
+/// let res = block_size(26, false).unwrap();
+/// assert_eq!(res, 64);
+/// ```
+pub fn block_size(prefix: u8, v6: bool) -> Result<u128, SubnetError> {
+    check_prefix(prefix, v6)?;
+    Ok(1u128.checked_shl((max_prefix(v6) - prefix) as u32).unwrap_or(u128::MAX))
+}
+
+
+/// The longest prefix whose subnets have at least `host_count` usable
+/// addresses, leaving out the network and broadcast addresses on IPv4 as in
+/// [`describe`](crate::describe). Returns [`SubnetError::TooLarge`] if even a
+/// `/0` is too small.
+///
+/// # Examples
+///
+/// ### Size a subnet for 100 hosts.
+///
+/// ```
+/// use subnet_utils::prefix_for_hosts;
+///
+/// let res = prefix_for_hosts(100, false).unwrap();
+/// assert_eq!(res, 25);
+/// ```
+pub fn prefix_for_hosts(host_count: u128, v6: bool) -> Result<u8, SubnetError> {
+    let usable = |prefix| {
+        let (first, last) = Subnet::from_bits(0, prefix, v6).usable();
+        last - first + 1
+    };
+    (0..=max_prefix(v6))
+        .rev()
+        .find(|prefix| usable(*prefix) >= host_count)
+        .ok_or(SubnetError::TooLarge { size: host_count, limit: usable(0) })
+}
+
+
+/// Check if `subnet` is a host route, a /32 for IPv4 or a /128 for IPv6.
+///
+/// # Examples
+///
+/// ### Check if a subnet is a host route.
+///
+/// ```
+/// use subnet_utils::is_host_route;
+///
+/// let res = is_host_route("192.168.182.1/32").unwrap();
+/// assert!(res);
+/// ```
+pub fn is_host_route(subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(subnet.prefix_len() == subnet.max_prefix())
+}
+
+
+/// Check if `addr` is the gateway of `subnet` under the given convention, see
+/// [`conventional_gateway`]. Subnets without a conventional gateway (/31, /32,
+/// /127 and /128) always return `false`.
+///
+/// # Examples
+///
+/// ### Check if an address is the conventional gateway.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{GatewayPosition, is_conventional_gateway};
+///
+/// let addr = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1));
+/// let res = is_conventional_gateway(&addr, "192.168.182.0/24", GatewayPosition::First).unwrap();
+/// assert!(res);
+/// ```
+pub fn is_conventional_gateway(addr: &IpAddr, subnet: &str, position: GatewayPosition) -> Result<bool, SubnetError> {
+    match conventional_gateway(subnet, position) {
+        Ok(gateway) => Ok(gateway == *addr),
+        Err(SubnetError::InvalidArgument(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+
+/// The network of `addr` as an integer, masked to `v4_prefix` for IPv4 and
+/// `v6_prefix` for IPv6, for use as a compact per-network key. Both prefixes
+/// are validated whichever family `addr` is.
+///
+/// IPv4 and IPv6 keys share the same integer space, so keep them apart if
+/// both families are counted together.
+///
+/// # Examples
+///
+/// ### Bucket an address into its /24.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::aggregation_key;
+///
+/// let res = aggregation_key(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 37)), 24, 48).unwrap();
+/// assert_eq!(res, u32::from(Ipv4Addr::new(10, 0, 0, 0)) as u128);
+/// ```
+pub fn aggregation_key(addr: &IpAddr, v4_prefix: u8, v6_prefix: u8) -> Result<u128, SubnetError> {
+    check_prefix(v4_prefix, false)?;
+    check_prefix(v6_prefix, true)?;
+    let prefix = if addr.is_ipv6() { v6_prefix } else { v4_prefix };
+    Ok(Subnet::from_parts(*addr, prefix)?.first())
+}
+
+
+/// A hash of the network of `addr` at `prefix` that is stable across runs,
+/// processes and platforms, e.g. for picking a color per network.
+///
+/// The hash is 64-bit FNV-1a over the family tag (`4` or `6`), the prefix
+/// length and the network address bytes in network order.
+///
+/// # Examples
+///
+/// ### Hash the /24 of two addresses.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::subnet_hash;
+///
+/// let a = subnet_hash(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 24).unwrap();
+/// let b = subnet_hash(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 254)), 24).unwrap();
+/// assert_eq!(a, b);
+/// ```
+pub fn subnet_hash(addr: &IpAddr, prefix: u8) -> Result<u64, SubnetError> {
+    let subnet = Subnet::from_parts(*addr, prefix)?;
+    let network = subnet.first().to_be_bytes();
+    let network = if subnet.is_ipv6() { &network[..] } else { &network[12..] };
+    let tag = if subnet.is_ipv6() { 6 } else { 4 };
+    Ok([tag, prefix]
+        .iter()
+        .chain(network)
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)))
+}
+
+
+/// Rolls `subnet` up to the block of length `v4_boundary` (IPv4) or
+/// `v6_boundary` (IPv6) that contains it. Subnets already at or broader than
+/// the boundary are returned unchanged. Both boundaries are validated
+/// whichever family `subnet` is.
+///
+/// # Examples
+///
+/// ### Roll a host up to its /24.
+///
+/// ```
+/// use subnet_utils::rollup;
+///
+/// let res = rollup("10.0.0.37/32", 24, 48).unwrap();
+/// assert_eq!(res, "10.0.0.0/24");
+/// ```
+pub fn rollup(subnet: &str, v4_boundary: u8, v6_boundary: u8) -> Result<String, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    check_prefix(v4_boundary, false)?;
+    check_prefix(v6_boundary, true)?;
+    let boundary = if subnet.is_ipv6() { v6_boundary } else { v4_boundary };
+    if subnet.prefix_len() <= boundary {
+        return Ok(subnet.to_string());
+    }
+    Ok(Subnet::from_bits(subnet.first(), boundary, subnet.is_ipv6()).to_string())
+}
+
+
+/// How a subnet's prefix lines up with the octet (IPv4) or nibble (IPv6)
+/// boundaries of its address notation, see [`alignment_info`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Alignment {
+    /// The boundary step: `8` bits for IPv4 and `4` bits for IPv6.
+    pub step: u8,
+    /// Whether the prefix is a multiple of `step`.
+    pub aligned: bool,
+    /// The nearest aligned prefix at or below the prefix, i.e. a wider subnet.
+    pub shorter: u8,
+    /// The nearest aligned prefix at or above the prefix, i.e. a narrower subnet.
+    pub longer: u8,
+}
+
+/// Check if the prefix of `subnet` falls on an octet boundary for IPv4 or a
+/// nibble boundary for IPv6, and find the nearest prefixes that do.
+///
+/// # Examples
+///
+/// ### Check if a subnet is octet-aligned.
+///
+/// ```
+/// use subnet_utils::alignment_info;
+///
+/// let res = alignment_info("10.0.0.0/20").unwrap();
+/// assert!(!res.aligned);
+/// assert_eq!((res.shorter, res.longer), (16, 24));
+/// ```
+pub fn alignment_info(subnet: &str) -> Result<Alignment, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let step = if subnet.is_ipv6() { 4 } else { 8 };
+    let shorter = subnet.prefix_len() - subnet.prefix_len() % step;
+    let aligned = shorter == subnet.prefix_len();
+    Ok(Alignment { step, aligned, shorter, longer: if aligned { shorter } else { shorter + step } })
+}
+
+
+/// Where an address sits within its subnet, see [`is_boundary_address`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BoundaryKind {
+    /// The network address, the first address of the subnet.
+    Network,
+    /// The broadcast address, or for IPv6 the last address of the subnet.
+    Broadcast,
+    /// Any other address.
+    Interior,
+}
+
+/// Whether `addr` is the network address, the broadcast (last) address or an
+/// interior address of the subnet of length `prefix` containing it. For a
+/// /32 or /128 the single address counts as the network address.
+///
+/// # Examples
+///
+/// ### Classify an address within its /24.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{BoundaryKind, is_boundary_address};
+///
+/// let res = is_boundary_address(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 255)), 24).unwrap();
+/// assert_eq!(res, BoundaryKind::Broadcast);
+/// ```
+pub fn is_boundary_address(addr: &IpAddr, prefix: u8) -> Result<BoundaryKind, SubnetError> {
+    let subnet = Subnet::from_parts(*addr, prefix)?;
+    let bits = addr_to_bits(addr);
+    Ok(if bits == subnet.first() {
+        BoundaryKind::Network
+    } else if bits == subnet.last() {
+        BoundaryKind::Broadcast
+    } else {
+        BoundaryKind::Interior
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_subnet() {
+        assert_eq!(next_subnet("10.0.0.0/24").unwrap().as_deref(), Some("10.0.1.0/24"));
+        assert_eq!(next_subnet("2001:db8::/64").unwrap().as_deref(), Some("2001:db8:0:1::/64"));
+        assert_eq!(next_subnet("255.255.255.0/24").unwrap(), None);
+        assert_eq!(next_subnet("::/0").unwrap(), None);
+    }
+
+    #[test]
+    fn test_prev_subnet() {
+        assert_eq!(prev_subnet("10.0.1.0/24").unwrap().as_deref(), Some("10.0.0.0/24"));
+        assert_eq!(prev_subnet("2001:db8:0:1::/64").unwrap().as_deref(), Some("2001:db8::/64"));
+        assert_eq!(prev_subnet("0.0.0.0/24").unwrap(), None);
+    }
+
+    #[test]
+    fn test_broadcast_for() {
+        let res = broadcast_for(&"10.0.0.37".parse().unwrap(), 24).unwrap();
+        assert_eq!(res, "10.0.0.255".parse::<IpAddr>().unwrap());
+        let res = broadcast_for(&"10.0.0.37".parse().unwrap(), 32).unwrap();
+        assert_eq!(res, "10.0.0.37".parse::<IpAddr>().unwrap());
+        let res = broadcast_for(&"2001:db8::1".parse().unwrap(), 64).unwrap();
+        assert_eq!(res, "2001:db8::ffff:ffff:ffff:ffff".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_broadcast_for_invalid_prefix() {
+        let res = broadcast_for(&"10.0.0.37".parse().unwrap(), 33);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+    }
+
+    #[test]
+    fn test_address_distance() {
+        let res = address_distance(&"10.0.0.1".parse().unwrap(), &"10.0.1.1".parse().unwrap()).unwrap();
+        assert_eq!(res, 256);
+        let res = address_distance(&"::".parse().unwrap(), &"ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()).unwrap();
+        assert_eq!(res, u128::MAX);
+    }
+
+    #[test]
+    fn test_address_distance_family_mismatch() {
+        let res = address_distance(&"10.0.0.1".parse().unwrap(), &"::1".parse().unwrap());
+        assert_eq!(res, Err(SubnetError::FamilyMismatch));
+    }
+
+    #[test]
+    fn test_same_broadcast_domain() {
+        let res = same_broadcast_domain(&"192.168.182.1".parse().unwrap(), &"192.168.182.254".parse().unwrap(), 24).unwrap();
+        assert!(res);
+        let res = same_broadcast_domain(&"2001:db8::1".parse().unwrap(), &"2001:db8::2".parse().unwrap(), 64).unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn test_not_same_broadcast_domain() {
+        let res = same_broadcast_domain(&"192.168.182.1".parse().unwrap(), &"192.168.183.1".parse().unwrap(), 24).unwrap();
+        assert!(!res);
+        let res = same_broadcast_domain(&"192.168.182.1".parse().unwrap(), &"192.168.182.1".parse().unwrap(), 32).unwrap();
+        assert!(!res);
+        let res = same_broadcast_domain(&"192.168.182.1".parse().unwrap(), &"::1".parse().unwrap(), 24).unwrap();
+        assert!(!res);
+        assert!(same_broadcast_domain(&"192.168.182.1".parse().unwrap(), &"192.168.182.2".parse().unwrap(), 33).is_err());
+    }
+
+    #[test]
+    fn test_same_broadcast_domain_invalid_prefix_mixed_families() {
+        let res = same_broadcast_domain(&"192.168.182.1".parse().unwrap(), &"::1".parse().unwrap(), 33);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+        let res = same_broadcast_domain(&"::1".parse().unwrap(), &"192.168.182.1".parse().unwrap(), 129);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 129, max: 128 }));
+    }
+
+    #[test]
+    fn test_mask_addr() {
+        let res = mask_addr(&"10.0.0.37".parse().unwrap(), 24).unwrap();
+        assert_eq!(res, "10.0.0.0".parse::<IpAddr>().unwrap());
+        let res = mask_addr(&"10.0.0.37".parse().unwrap(), 0).unwrap();
+        assert_eq!(res, "0.0.0.0".parse::<IpAddr>().unwrap());
+        let res = mask_addr(&"2001:db8::1".parse().unwrap(), 32).unwrap();
+        assert_eq!(res, "2001:db8::".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_mask_addr_invalid_prefix() {
+        let res = mask_addr(&"2001:db8::1".parse().unwrap(), 129);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 129, max: 128 }));
+    }
+
+    #[test]
+    fn test_is_last_address() {
+        assert!(is_last_address(&"192.168.182.255".parse().unwrap(), "192.168.182.0/24").unwrap());
+        assert!(is_last_address(&"2001:db8::ffff".parse().unwrap(), "2001:db8::/112").unwrap());
+        assert!(is_last_address(&"192.168.182.1".parse().unwrap(), "192.168.182.1/32").unwrap());
+    }
+
+    #[test]
+    fn test_is_not_last_address() {
+        assert!(!is_last_address(&"192.168.182.254".parse().unwrap(), "192.168.182.0/24").unwrap());
+        assert!(!is_last_address(&"192.168.183.255".parse().unwrap(), "192.168.182.0/24").unwrap());
+        assert!(!is_last_address(&"::ffff".parse().unwrap(), "0.0.0.0/16").unwrap());
+    }
+
+    #[test]
+    fn test_is_point_to_point() {
+        assert!(is_point_to_point("10.0.0.0/31").unwrap());
+        assert!(is_point_to_point("2001:db8::/127").unwrap());
+    }
+
+    #[test]
+    fn test_is_not_point_to_point() {
+        assert!(!is_point_to_point("10.0.0.0/30").unwrap());
+        assert!(!is_point_to_point("10.0.0.0/32").unwrap());
+        assert!(!is_point_to_point("2001:db8::/31").unwrap());
+        assert!(is_point_to_point("zoop").is_err());
+    }
+
+    #[test]
+    fn test_host_bits_to_prefix() {
+        assert_eq!(host_bits_to_prefix(8, false).unwrap(), 24);
+        assert_eq!(host_bits_to_prefix(0, false).unwrap(), 32);
+        assert_eq!(host_bits_to_prefix(128, true).unwrap(), 0);
+        assert!(matches!(host_bits_to_prefix(33, false), Err(SubnetError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_prefix_to_host_bits() {
+        assert_eq!(prefix_to_host_bits(24, false).unwrap(), 8);
+        assert_eq!(prefix_to_host_bits(64, true).unwrap(), 64);
+        assert_eq!(prefix_to_host_bits(33, false), Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+    }
+
+    #[test]
+    fn test_conventional_gateway() {
+        let res = conventional_gateway("192.168.182.0/24", GatewayPosition::Last).unwrap();
+        assert_eq!(res, "192.168.182.254".parse::<IpAddr>().unwrap());
+        let res = conventional_gateway("10.0.0.4/30", GatewayPosition::First).unwrap();
+        assert_eq!(res, "10.0.0.5".parse::<IpAddr>().unwrap());
+        let res = conventional_gateway("2001:db8::/64", GatewayPosition::First).unwrap();
+        assert_eq!(res, "2001:db8::1".parse::<IpAddr>().unwrap());
+        let res = conventional_gateway("2001:db8::/64", GatewayPosition::Last).unwrap();
+        assert_eq!(res, "2001:db8::ffff:ffff:ffff:ffff".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_conventional_gateway_degenerate() {
+        assert!(matches!(conventional_gateway("10.0.0.0/31", GatewayPosition::First), Err(SubnetError::InvalidArgument(_))));
+        assert!(matches!(conventional_gateway("10.0.0.1/32", GatewayPosition::Last), Err(SubnetError::InvalidArgument(_))));
+        assert!(conventional_gateway("2001:db8::/127", GatewayPosition::First).is_err());
+        assert!(conventional_gateway("2001:db8::1/128", GatewayPosition::First).is_err());
+    }
+
+    #[test]
+    fn test_mask_bits() {
+        let res = mask_bits("10.0.0.0/30").unwrap();
+        assert_eq!(res.len(), 32);
+        assert!(res[..30].iter().all(|b| *b) && !res[30] && !res[31]);
+        let res = mask_bits("2001:db8::/32").unwrap();
+        assert_eq!(res.len(), 128);
+        assert_eq!(res.iter().position(|b| !*b), Some(32));
+        assert!(mask_bits("0.0.0.0/0").unwrap().iter().all(|b| !*b));
+    }
+
+    #[test]
+    fn test_mask_bits_non_contiguous() {
+        assert!(mask_bits("10.0.0.0/255.0.255.0").is_err());
+    }
+
+    #[test]
+    fn test_block_size() {
+        assert_eq!(block_size(24, false).unwrap(), 256);
+        assert_eq!(block_size(32, false).unwrap(), 1);
+        assert_eq!(block_size(0, false).unwrap(), 1 << 32);
+        assert_eq!(block_size(64, true).unwrap(), 1 << 64);
+        assert_eq!(block_size(0, true).unwrap(), u128::MAX);
+    }
+
+    #[test]
+    fn test_block_size_invalid_prefix() {
+        assert_eq!(block_size(33, false), Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+        assert_eq!(block_size(129, true), Err(SubnetError::InvalidPrefix { prefix: 129, max: 128 }));
+    }
+
+    #[test]
+    fn test_is_host_route() {
+        assert!(is_host_route("192.168.182.1/32").unwrap());
+        assert!(is_host_route("2001:db8::1/128").unwrap());
+    }
+
+    #[test]
+    fn test_is_not_host_route() {
+        assert!(!is_host_route("192.168.182.0/31").unwrap());
+        assert!(!is_host_route("2001:db8::/32").unwrap());
+        assert!(is_host_route("zoop").is_err());
+    }
+
+    #[test]
+    fn test_is_conventional_gateway() {
+        let addr = "192.168.182.254".parse().unwrap();
+        assert!(is_conventional_gateway(&addr, "192.168.182.0/24", GatewayPosition::Last).unwrap());
+        assert!(!is_conventional_gateway(&addr, "192.168.182.0/24", GatewayPosition::First).unwrap());
+        let addr = "2001:db8::1".parse().unwrap();
+        assert!(is_conventional_gateway(&addr, "2001:db8::/64", GatewayPosition::First).unwrap());
+    }
+
+    #[test]
+    fn test_is_conventional_gateway_degenerate() {
+        let addr = "10.0.0.1".parse().unwrap();
+        assert!(!is_conventional_gateway(&addr, "10.0.0.0/31", GatewayPosition::Last).unwrap());
+        assert!(!is_conventional_gateway(&addr, "10.0.0.1/32", GatewayPosition::First).unwrap());
+        assert!(is_conventional_gateway(&addr, "zoop", GatewayPosition::First).is_err());
+    }
+
+    #[test]
+    fn test_aggregation_key() {
+        let a = aggregation_key(&"10.0.0.37".parse().unwrap(), 24, 48).unwrap();
+        let b = aggregation_key(&"10.0.0.200".parse().unwrap(), 24, 48).unwrap();
+        let c = aggregation_key(&"10.0.1.1".parse().unwrap(), 24, 48).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        let res = aggregation_key(&"2001:db8:1:2::1".parse().unwrap(), 24, 48).unwrap();
+        assert_eq!(res, u128::from("2001:db8:1::".parse::<std::net::Ipv6Addr>().unwrap()));
+    }
+
+    #[test]
+    fn test_aggregation_key_invalid_prefix() {
+        let res = aggregation_key(&"10.0.0.37".parse().unwrap(), 24, 129);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 129, max: 128 }));
+        let res = aggregation_key(&"2001:db8::1".parse().unwrap(), 33, 48);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+    }
+
+    #[test]
+    fn test_subnet_hash() {
+        let res = subnet_hash(&"10.0.0.1".parse().unwrap(), 24).unwrap();
+        assert_eq!(res, 0x3fdf_0b2d_e574_62eb);
+        let res = subnet_hash(&"2001:db8::1".parse().unwrap(), 48).unwrap();
+        assert_eq!(res, subnet_hash(&"2001:db8:0:ffff::".parse().unwrap(), 48).unwrap());
+    }
+
+    #[test]
+    fn test_subnet_hash_distinct() {
+        let a = subnet_hash(&"10.0.0.1".parse().unwrap(), 24).unwrap();
+        assert_ne!(a, subnet_hash(&"10.0.1.1".parse().unwrap(), 24).unwrap());
+        assert_ne!(a, subnet_hash(&"10.0.0.1".parse().unwrap(), 25).unwrap());
+        assert_ne!(a, subnet_hash(&"::a00:1".parse().unwrap(), 120).unwrap());
+        assert!(subnet_hash(&"10.0.0.1".parse().unwrap(), 33).is_err());
+    }
+
+    #[test]
+    fn test_rollup() {
+        assert_eq!(rollup("10.0.0.37/32", 24, 48).unwrap(), "10.0.0.0/24");
+        assert_eq!(rollup("10.0.0.128/25", 24, 48).unwrap(), "10.0.0.0/24");
+        assert_eq!(rollup("2001:db8:1:2::/64", 24, 48).unwrap(), "2001:db8:1::/48");
+    }
+
+    #[test]
+    fn test_rollup_broader() {
+        assert_eq!(rollup("10.0.0.0/16", 24, 48).unwrap(), "10.0.0.0/16");
+        assert_eq!(rollup("10.0.0.0/24", 24, 48).unwrap(), "10.0.0.0/24");
+        assert_eq!(rollup("10.0.0.0/24", 24, 129), Err(SubnetError::InvalidPrefix { prefix: 129, max: 128 }));
+    }
+
+    #[test]
+    fn test_prefix_for_hosts() {
+        assert_eq!(prefix_for_hosts(100, false).unwrap(), 25);
+        assert_eq!(prefix_for_hosts(126, false).unwrap(), 25);
+        assert_eq!(prefix_for_hosts(127, false).unwrap(), 24);
+        assert_eq!(prefix_for_hosts(3, false).unwrap(), 29);
+        assert_eq!(prefix_for_hosts(2, false).unwrap(), 31);
+        assert_eq!(prefix_for_hosts(1, false).unwrap(), 32);
+        assert_eq!(prefix_for_hosts(0, false).unwrap(), 32);
+        assert_eq!(prefix_for_hosts(100, true).unwrap(), 121);
+        assert_eq!(prefix_for_hosts(1 << 64, true).unwrap(), 63);
+    }
+
+    #[test]
+    fn test_prefix_for_hosts_too_large() {
+        assert_eq!(prefix_for_hosts(1 << 32, false), Err(SubnetError::TooLarge { size: 1 << 32, limit: (1 << 32) - 2 }));
+        assert!(prefix_for_hosts((1 << 32) - 2, false).is_ok());
+        assert_eq!(prefix_for_hosts(u128::MAX, true).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_alignment_info() {
+        let res = alignment_info("10.0.0.0/24").unwrap();
+        assert_eq!(res, Alignment { step: 8, aligned: true, shorter: 24, longer: 24 });
+        let res = alignment_info("0.0.0.0/0").unwrap();
+        assert_eq!(res, Alignment { step: 8, aligned: true, shorter: 0, longer: 0 });
+        let res = alignment_info("2001:db8::/48").unwrap();
+        assert_eq!(res, Alignment { step: 4, aligned: true, shorter: 48, longer: 48 });
+    }
+
+    #[test]
+    fn test_alignment_info_unaligned() {
+        let res = alignment_info("10.0.0.0/31").unwrap();
+        assert_eq!(res, Alignment { step: 8, aligned: false, shorter: 24, longer: 32 });
+        let res = alignment_info("2001:db8::/127").unwrap();
+        assert_eq!(res, Alignment { step: 4, aligned: false, shorter: 124, longer: 128 });
+        let res = alignment_info("::/1").unwrap();
+        assert_eq!(res, Alignment { step: 4, aligned: false, shorter: 0, longer: 4 });
+        assert!(alignment_info("zoop").is_err());
+    }
+
+    #[test]
+    fn test_is_boundary_address() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(is_boundary_address(&addr("10.0.0.0"), 8).unwrap(), BoundaryKind::Network);
+        assert_eq!(is_boundary_address(&addr("10.255.255.255"), 8).unwrap(), BoundaryKind::Broadcast);
+        assert_eq!(is_boundary_address(&addr("10.0.0.1"), 8).unwrap(), BoundaryKind::Interior);
+        assert_eq!(is_boundary_address(&addr("10.0.0.1"), 31).unwrap(), BoundaryKind::Broadcast);
+        assert_eq!(is_boundary_address(&addr("10.0.0.1"), 32).unwrap(), BoundaryKind::Network);
+    }
+
+    #[test]
+    fn test_is_boundary_address_ipv6() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(is_boundary_address(&addr("2001:db8::"), 64).unwrap(), BoundaryKind::Network);
+        assert_eq!(is_boundary_address(&addr("2001:db8::ffff:ffff:ffff:ffff"), 64).unwrap(), BoundaryKind::Broadcast);
+        assert_eq!(is_boundary_address(&addr("2001:db8::1"), 64).unwrap(), BoundaryKind::Interior);
+        assert!(matches!(is_boundary_address(&addr("2001:db8::1"), 129), Err(SubnetError::InvalidPrefix { .. })));
+    }
+}
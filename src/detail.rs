@@ -0,0 +1,365 @@
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, addr_to_bits, bits_to_addr};
+
+
+/// The result of [`match_detail`]: whether an address matched a list of
+/// subnets and, if so, which one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchDetail {
+    /// Whether any subnet contained the address.
+    pub matched: bool,
+    /// The index of the first matching subnet in the list.
+    pub index: Option<usize>,
+    /// The matching subnet in canonical CIDR form.
+    pub subnet: Option<String>,
+    /// The prefix length of the matching subnet.
+    pub prefix_len: Option<u8>,
+    /// The number of subnets checked, which is the list length when nothing matched.
+    pub scanned: usize,
+}
+
+/// Counts from [`summarize_addrs`] of how many addresses matched a list of subnets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchSummary {
+    /// The number of addresses checked.
+    pub total: usize,
+    /// The number of addresses contained in at least one subnet.
+    pub matched: usize,
+    /// The number of addresses contained in none of the subnets.
+    pub unmatched: usize,
+    /// For each subnet, the number of addresses it was the first match for.
+    pub per_subnet: Vec<usize>,
+}
+
+/// Like [`addr_in_any_subnet`](crate::addr_in_any_subnet), but reports which
+/// subnet matched. The first matching subnet in the list wins.
+///
+/// # Examples
+///
+/// ### Find which subnet contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::match_detail;
+///
+/// let subnets = vec!["192.168.181.0/24", "192.168.182.0/24"];
+/// let res = match_detail(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
+/// assert!(res.matched);
+/// assert_eq!(res.index, Some(1));
+/// assert_eq!(res.subnet.as_deref(), Some("192.168.182.0/24"));
+/// ```
+pub fn match_detail(addr: &IpAddr, subnets: &[&str]) -> Result<MatchDetail, SubnetError> {
+    for (index, subnet) in subnets.iter().enumerate() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.contains(addr) {
+            return Ok(MatchDetail {
+                matched: true,
+                index: Some(index),
+                subnet: Some(subnet.to_string()),
+                prefix_len: Some(subnet.prefix_len()),
+                scanned: index + 1,
+            });
+        }
+    }
+    Ok(MatchDetail { matched: false, index: None, subnet: None, prefix_len: None, scanned: subnets.len() })
+}
+
+
+/// The number of `subnets` that contain `addr`, i.e. how many nested or
+/// overlapping policies apply to it, or `None` if none do.
+///
+/// # Examples
+///
+/// ### Count how many subnets contain an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::match_depth;
+///
+/// let subnets = vec!["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24", "10.2.0.0/16"];
+/// let res = match_depth(&IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)), &subnets).unwrap();
+/// assert_eq!(res, Some(3));
+/// ```
+pub fn match_depth(addr: &IpAddr, subnets: &[&str]) -> Result<Option<usize>, SubnetError> {
+    let mut depth = 0;
+    for subnet in subnets.iter() {
+        if subnet.parse::<Subnet>()?.contains(addr) {
+            depth += 1;
+        }
+    }
+    Ok(Some(depth).filter(|depth| *depth > 0))
+}
+
+
+/// The first of `subnets` that contains `addr`, borrowed from the input as
+/// written, or `None` if none do.
+///
+/// # Examples
+///
+/// ### Find the subnet string that contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::first_matching_subnet_str;
+///
+/// let subnets = vec!["192.168.181.0/24", "192.168.182.0/24"];
+/// let res = first_matching_subnet_str(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
+/// assert_eq!(res, Some("192.168.182.0/24"));
+/// ```
+pub fn first_matching_subnet_str<'a>(addr: &IpAddr, subnets: &'a [&'a str]) -> Result<Option<&'a str>, SubnetError> {
+    for subnet in subnets.iter() {
+        if subnet.parse::<Subnet>()?.contains(addr) {
+            return Ok(Some(subnet));
+        }
+    }
+    Ok(None)
+}
+
+
+/// The index of the subnet whose network address is numerically closest to
+/// `addr`, for suggesting a likely block when nothing matches. Subnets of the
+/// other family are skipped, ties go to the earlier subnet, and `None` is
+/// returned if no subnet is the same family as `addr`.
+///
+/// # Examples
+///
+/// ### Find the subnet nearest to an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::nearest_enclosing;
+///
+/// let subnets = vec!["10.0.0.0/24", "10.0.8.0/24", "2001:db8::/32"];
+/// let res = nearest_enclosing(&IpAddr::V4(Ipv4Addr::new(10, 0, 7, 1)), &subnets).unwrap();
+/// assert_eq!(res, Some(1));
+/// ```
+pub fn nearest_enclosing(addr: &IpAddr, subnets: &[&str]) -> Result<Option<usize>, SubnetError> {
+    let bits = addr_to_bits(addr);
+    let mut nearest: Option<(u128, usize)> = None;
+    for (index, subnet) in subnets.iter().enumerate() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.is_ipv6() != addr.is_ipv6() {
+            continue;
+        }
+        let distance = bits.abs_diff(subnet.first());
+        if nearest.is_none_or(|(best, _)| distance < best) {
+            nearest = Some((distance, index));
+        }
+    }
+    Ok(nearest.map(|(_, index)| index))
+}
+
+
+/// A human-readable explanation of whether `subnet` contains `addr`: the
+/// address is contained, is the other family, or falls below or above the
+/// subnet's range.
+///
+/// # Examples
+///
+/// ### Explain why a subnet doesn't contain an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::explain_mismatch;
+///
+/// let res = explain_mismatch(&IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1)), "192.168.182.0/24").unwrap();
+/// assert_eq!(res, "192.168.183.1 is above 192.168.182.0/24, which ends at 192.168.182.255");
+/// ```
+pub fn explain_mismatch(addr: &IpAddr, subnet: &str) -> Result<String, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let family = |v6| if v6 { "IPv6" } else { "IPv4" };
+    if addr.is_ipv6() != subnet.is_ipv6() {
+        return Ok(format!("{} is {} but {} is {}", addr, family(addr.is_ipv6()), subnet, family(subnet.is_ipv6())));
+    }
+    let bits = addr_to_bits(addr);
+    if bits < subnet.first() {
+        Ok(format!("{} is below {}, which starts at {}", addr, subnet, subnet.network()))
+    } else if bits > subnet.last() {
+        Ok(format!("{} is above {}, which ends at {}", addr, subnet, bits_to_addr(subnet.last(), subnet.is_ipv6())))
+    } else {
+        Ok(format!("{} is contained in {}", addr, subnet))
+    }
+}
+
+/// Checks each of `addrs` against `subnets` and counts the results, parsing
+/// the subnets only once. Each matched address is counted against the first
+/// subnet that contains it.
+///
+/// # Examples
+///
+/// ### Summarize which subnets a batch of addresses falls in.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::summarize_addrs;
+///
+/// let addrs = (1..=4).map(|i| IpAddr::V4(Ipv4Addr::new(192, 168, 182, i)));
+/// let res = summarize_addrs(addrs, &["192.168.182.0/31", "192.168.182.0/24", "10.0.0.0/8"]).unwrap();
+/// assert_eq!((res.total, res.matched, res.unmatched), (4, 4, 0));
+/// assert_eq!(res.per_subnet, vec![1, 3, 0]);
+/// ```
+pub fn summarize_addrs(addrs: impl IntoIterator<Item = IpAddr>, subnets: &[&str]) -> Result<MatchSummary, SubnetError> {
+    let parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    let mut summary = MatchSummary { per_subnet: vec![0; parsed.len()], ..MatchSummary::default() };
+    for addr in addrs {
+        summary.total += 1;
+        match parsed.iter().position(|subnet| subnet.contains(&addr)) {
+            Some(index) => {
+                summary.matched += 1;
+                summary.per_subnet[index] += 1;
+            }
+            None => summary.unmatched += 1,
+        }
+    }
+    Ok(summary)
+}
+
+/// A matrix whose entry `[i][j]` is whether `addrs[i]` is in `subnets[j]`,
+/// parsing the subnets only once.
+///
+/// This checks every pair, so it costs `O(n * m)` and is meant for modest
+/// inputs such as a report. For large lists, build a [`SubnetSet`] and query
+/// it per address instead.
+///
+/// [`SubnetSet`]: crate::SubnetSet
+///
+/// # Examples
+///
+/// ### Map each address to the subnets containing it.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::match_matrix;
+///
+/// let addrs = [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1))];
+/// let res = match_matrix(&addrs, &["10.0.0.0/8", "10.0.0.0/24", "172.16.0.0/12"]).unwrap();
+/// assert_eq!(res, vec![vec![true, true, false], vec![false, false, false]]);
+/// ```
+pub fn match_matrix(addrs: &[IpAddr], subnets: &[&str]) -> Result<Vec<Vec<bool>>, SubnetError> {
+    let parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    Ok(addrs.iter().map(|addr| parsed.iter().map(|subnet| subnet.contains(addr)).collect()).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_detail() {
+        let subnets = vec!["10.0.0.0/8", "192.168.182.1/24", "192.168.0.0/16"];
+        let res = match_detail(&"192.168.182.1".parse().unwrap(), &subnets).unwrap();
+        let expected = MatchDetail {
+            matched: true,
+            index: Some(1),
+            subnet: Some("192.168.182.0/24".to_string()),
+            prefix_len: Some(24),
+            scanned: 2,
+        };
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_match_detail_no_match() {
+        let subnets = vec!["10.0.0.0/8", "::/0"];
+        let res = match_detail(&"192.168.182.1".parse().unwrap(), &subnets).unwrap();
+        assert!(!res.matched);
+        assert_eq!(res.index, None);
+        assert_eq!(res.scanned, 2);
+        assert!(match_detail(&"192.168.182.1".parse().unwrap(), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_match_depth() {
+        let subnets = vec!["10.1.2.0/24", "0.0.0.0/0", "10.0.0.0/8", "::/0", "10.1.0.0/16"];
+        assert_eq!(match_depth(&"10.1.2.3".parse().unwrap(), &subnets).unwrap(), Some(4));
+        assert_eq!(match_depth(&"10.2.0.1".parse().unwrap(), &subnets).unwrap(), Some(2));
+        assert_eq!(match_depth(&"2001:db8::1".parse().unwrap(), &subnets).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_match_depth_no_match() {
+        let subnets = vec!["10.0.0.0/8", "10.1.0.0/16"];
+        assert_eq!(match_depth(&"192.168.182.1".parse().unwrap(), &subnets).unwrap(), None);
+        assert_eq!(match_depth(&"192.168.182.1".parse().unwrap(), &[]).unwrap(), None);
+        assert!(match_depth(&"10.0.0.1".parse().unwrap(), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_first_matching_subnet_str() {
+        let subnets = vec!["10.0.0.0/8", " 192.168.182.1/24", "192.168.0.0/16"];
+        let res = first_matching_subnet_str(&"192.168.182.1".parse().unwrap(), &subnets).unwrap();
+        assert_eq!(res, Some(" 192.168.182.1/24"));
+    }
+
+    #[test]
+    fn test_first_matching_subnet_str_no_match() {
+        let subnets = vec!["10.0.0.0/8", "::/0"];
+        assert_eq!(first_matching_subnet_str(&"192.168.182.1".parse().unwrap(), &subnets).unwrap(), None);
+        assert!(first_matching_subnet_str(&"192.168.182.1".parse().unwrap(), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_nearest_enclosing() {
+        let subnets = vec!["10.0.0.0/24", "::/0", "10.0.2.0/24", "10.0.4.0/24", "10.0.2.0/25"];
+        assert_eq!(nearest_enclosing(&"10.0.3.0".parse().unwrap(), &subnets).unwrap(), Some(2));
+        assert_eq!(nearest_enclosing(&"10.0.3.128".parse().unwrap(), &subnets).unwrap(), Some(3));
+        assert_eq!(nearest_enclosing(&"2001:db8::1".parse().unwrap(), &subnets).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_nearest_enclosing_none() {
+        let subnets = vec!["2001:db8::/32"];
+        assert_eq!(nearest_enclosing(&"10.0.3.0".parse().unwrap(), &subnets).unwrap(), None);
+        assert_eq!(nearest_enclosing(&"10.0.3.0".parse().unwrap(), &[]).unwrap(), None);
+        assert!(nearest_enclosing(&"10.0.3.0".parse().unwrap(), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_explain_mismatch() {
+        let res = explain_mismatch(&"10.0.0.255".parse().unwrap(), "10.0.1.0/24").unwrap();
+        assert_eq!(res, "10.0.0.255 is below 10.0.1.0/24, which starts at 10.0.1.0");
+        let res = explain_mismatch(&"10.0.2.0".parse().unwrap(), "10.0.1.0/24").unwrap();
+        assert_eq!(res, "10.0.2.0 is above 10.0.1.0/24, which ends at 10.0.1.255");
+        let res = explain_mismatch(&"::1".parse().unwrap(), "10.0.1.0/24").unwrap();
+        assert_eq!(res, "::1 is IPv6 but 10.0.1.0/24 is IPv4");
+    }
+
+    #[test]
+    fn test_explain_mismatch_contained() {
+        let res = explain_mismatch(&"2001:db8::1".parse().unwrap(), "2001:db8::/32").unwrap();
+        assert_eq!(res, "2001:db8::1 is contained in 2001:db8::/32");
+        assert!(explain_mismatch(&"::1".parse().unwrap(), "zoop").is_err());
+    }
+
+    #[test]
+    fn test_summarize_addrs() {
+        let addrs: Vec<IpAddr> = ["10.0.0.1", "10.1.0.1", "192.168.182.1", "2001:db8::1", "::1"].iter().map(|a| a.parse().unwrap()).collect();
+        let res = summarize_addrs(addrs, &["10.0.0.0/16", "10.0.0.0/8", "2001:db8::/32"]).unwrap();
+        let expected = MatchSummary { total: 5, matched: 3, unmatched: 2, per_subnet: vec![1, 1, 1] };
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_summarize_addrs_empty() {
+        let res = summarize_addrs(Vec::new(), &["10.0.0.0/8"]).unwrap();
+        assert_eq!(res, MatchSummary { total: 0, matched: 0, unmatched: 0, per_subnet: vec![0] });
+        assert!(summarize_addrs(Vec::new(), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_match_matrix() {
+        let addrs: Vec<IpAddr> = ["10.0.0.1", "2001:db8::1", "10.1.0.1"].iter().map(|s| s.parse().unwrap()).collect();
+        let res = match_matrix(&addrs, &["10.0.0.0/16", "2001:db8::/32", "10.0.0.0/8"]).unwrap();
+        assert_eq!(res, vec![vec![true, false, true], vec![false, true, false], vec![false, false, true]]);
+        assert_eq!(match_matrix(&addrs, &[]).unwrap(), vec![Vec::<bool>::new(); 3]);
+        assert!(match_matrix(&[], &["10.0.0.0/8"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_match_matrix_invalid() {
+        assert!(match_matrix(&[], &["10.0.0.0/8", "zoop"]).is_err());
+    }
+}
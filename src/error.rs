@@ -0,0 +1,54 @@
+use std::error::Error;
+use std::fmt;
+
+use netaddr2::Error as NetError;
+
+
+/// The error type for the subnet functions in this crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubnetError {
+    /// The input could not be parsed as a subnet or address.
+    ParseError(String),
+    /// Binary data could not be decoded.
+    DecodeError(String),
+    /// A prefix length is out of range for the address family.
+    InvalidPrefix { prefix: u8, max: u8 },
+    /// The address family is not the one the function expects.
+    FamilyMismatch,
+    /// The start of an address range is after its end.
+    InvalidRange,
+    /// A result would be larger than the allowed limit.
+    TooLarge { size: u128, limit: u128 },
+    /// An argument is outside the values the function accepts.
+    InvalidArgument(String),
+    /// A netmask's network bits are not contiguous, so it has no prefix length.
+    NonContiguousMask { mask: String },
+}
+
+impl fmt::Display for SubnetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubnetError::ParseError(text) => write!(f, "unable to parse subnet: {}", text),
+            SubnetError::DecodeError(text) => write!(f, "unable to decode subnets: {}", text),
+            SubnetError::InvalidPrefix { prefix, max } => {
+                write!(f, "invalid prefix length /{}, maximum is /{}", prefix, max)
+            }
+            SubnetError::FamilyMismatch => write!(f, "address family mismatch"),
+            SubnetError::InvalidRange => write!(f, "range start is after range end"),
+            SubnetError::TooLarge { size, limit } => write!(f, "size {} exceeds the limit of {}", size, limit),
+            SubnetError::InvalidArgument(text) => write!(f, "invalid argument: {}", text),
+            SubnetError::NonContiguousMask { mask } => write!(f, "netmask {} is not contiguous", mask),
+        }
+    }
+}
+
+impl Error for SubnetError {}
+
+impl From<NetError> for SubnetError {
+    fn from(err: NetError) -> Self {
+        match err {
+            NetError::ParseError(text) => SubnetError::ParseError(text),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::SubnetError;
+use crate::subnet::{Subnet, check_prefix};
+
+
+/// A prefix-list entry matching subnets within `base` by prefix length.
+///
+/// As with IOS/JunOS prefix lists, a filter without bounds only matches
+/// `base`'s own prefix length, `ge` alone matches up to the family maximum,
+/// and `le` alone matches from `base`'s prefix length.
+///
+/// # Examples
+///
+/// ### Check if a subnet matches a prefix filter.
+///
+/// ```
+/// use subnet_utils::PrefixFilter;
+///
+/// let filter = PrefixFilter::new("10.0.0.0/8".parse().unwrap(), Some(16), Some(24)).unwrap();
+/// assert!(filter.matches("10.20.0.0/16").unwrap());
+/// assert!(!filter.matches("10.20.30.0/28").unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PrefixFilter {
+    base: Subnet,
+    ge: Option<u8>,
+    le: Option<u8>,
+}
+
+impl PrefixFilter {
+    /// Create a filter, validating that `ge <= le` and both are valid prefix
+    /// lengths for `base`'s family.
+    pub fn new(base: Subnet, ge: Option<u8>, le: Option<u8>) -> Result<Self, SubnetError> {
+        let max = base.max_prefix();
+        for prefix in [ge, le].into_iter().flatten() {
+            if prefix > max {
+                return Err(SubnetError::InvalidPrefix { prefix, max });
+            }
+        }
+        if let (Some(ge), Some(le)) = (ge, le)
+            && ge > le
+        {
+            return Err(SubnetError::InvalidArgument(format!("ge /{} is greater than le /{}", ge, le)));
+        }
+        Ok(PrefixFilter { base, ge, le })
+    }
+
+    /// The subnet that matching candidates must fall within.
+    pub fn base(&self) -> Subnet {
+        self.base
+    }
+
+    /// The minimum prefix length, if any.
+    pub fn ge(&self) -> Option<u8> {
+        self.ge
+    }
+
+    /// The maximum prefix length, if any.
+    pub fn le(&self) -> Option<u8> {
+        self.le
+    }
+
+    /// Check if `subnet` is within `base` and its prefix length is within the bounds.
+    pub fn matches(&self, subnet: &str) -> Result<bool, SubnetError> {
+        let subnet: Subnet = subnet.parse()?;
+        let (min, max) = match (self.ge, self.le) {
+            (None, None) => (self.base.prefix_len(), self.base.prefix_len()),
+            (Some(ge), None) => (ge, self.base.max_prefix()),
+            (None, Some(le)) => (self.base.prefix_len(), le),
+            (Some(ge), Some(le)) => (ge, le),
+        };
+        let prefix = subnet.prefix_len();
+        Ok(self.base.contains_subnet(&subnet) && prefix >= min && prefix <= max)
+    }
+}
+
+
+/// Check if the prefix length of `subnet` is within the inclusive policy
+/// window `min_prefix..=max_prefix`, e.g. `/48` to `/64` for IPv6 assignments.
+///
+/// Both bounds must be valid for the subnet's family and `min_prefix` must not
+/// be greater than `max_prefix`.
+///
+/// # Examples
+///
+/// ### Check a subnet against an allocation policy.
+///
+/// ```
+/// use subnet_utils::prefix_within_policy;
+///
+/// assert!(prefix_within_policy("2001:db8:1::/56", 48, 64).unwrap());
+/// assert!(!prefix_within_policy("2001:db8::/32", 48, 64).unwrap());
+/// ```
+pub fn prefix_within_policy(subnet: &str, min_prefix: u8, max_prefix: u8) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    check_prefix(min_prefix, subnet.is_ipv6())?;
+    check_prefix(max_prefix, subnet.is_ipv6())?;
+    if min_prefix > max_prefix {
+        return Err(SubnetError::InvalidArgument(format!(
+            "minimum prefix /{} is greater than maximum prefix /{}",
+            min_prefix, max_prefix
+        )));
+    }
+    Ok((min_prefix..=max_prefix).contains(&subnet.prefix_len()))
+}
+
+/// Check if `subnet` has at most `max_addresses` addresses, e.g. `65_536` to
+/// reject anything broader than an IPv4 `/16`. Unlike
+/// [`prefix_within_policy`], the limit is an address count, so the same limit
+/// applies to both families.
+///
+/// # Examples
+///
+/// ### Reject subnets broader than a /16.
+///
+/// ```
+/// use subnet_utils::within_max_size;
+///
+/// assert!(within_max_size("10.0.0.0/16", 65_536).unwrap());
+/// assert!(!within_max_size("10.0.0.0/15", 65_536).unwrap());
+/// ```
+pub fn within_max_size(subnet: &str, max_addresses: u128) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    // `::/0` has 2^128 addresses, more than any `u128` limit.
+    let size = 1u128.checked_shl((subnet.max_prefix() - subnet.prefix_len()) as u32);
+    Ok(size.is_some_and(|size| size <= max_addresses))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_filter_matches() {
+        let filter = PrefixFilter::new("10.0.0.0/8".parse().unwrap(), Some(16), Some(24)).unwrap();
+        assert!(filter.matches("10.20.0.0/16").unwrap());
+        assert!(filter.matches("10.20.30.0/24").unwrap());
+        assert!(!filter.matches("10.20.30.0/25").unwrap());
+        assert!(!filter.matches("10.0.0.0/8").unwrap());
+        assert!(!filter.matches("11.0.0.0/16").unwrap());
+        assert!(!filter.matches("2001:db8::/32").unwrap());
+    }
+
+    #[test]
+    fn test_prefix_filter_default_bounds() {
+        let base: Subnet = "10.0.0.0/8".parse().unwrap();
+        let exact = PrefixFilter::new(base, None, None).unwrap();
+        assert!(exact.matches("10.0.0.0/8").unwrap());
+        assert!(!exact.matches("10.0.0.0/9").unwrap());
+        let ge = PrefixFilter::new(base, Some(24), None).unwrap();
+        assert!(ge.matches("10.0.0.1/32").unwrap());
+        let le = PrefixFilter::new(base, None, Some(12)).unwrap();
+        assert!(le.matches("10.0.0.0/8").unwrap());
+        assert!(!le.matches("10.0.0.0/13").unwrap());
+    }
+
+    #[test]
+    fn test_prefix_filter_invalid() {
+        let base: Subnet = "10.0.0.0/8".parse().unwrap();
+        assert_eq!(PrefixFilter::new(base, Some(33), None), Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+        assert!(PrefixFilter::new(base, Some(24), Some(16)).is_err());
+    }
+
+    #[test]
+    fn test_prefix_within_policy() {
+        assert!(prefix_within_policy("2001:db8::/48", 48, 64).unwrap());
+        assert!(prefix_within_policy("2001:db8::/64", 48, 64).unwrap());
+        assert!(!prefix_within_policy("2001:db8::/65", 48, 64).unwrap());
+        assert!(prefix_within_policy("10.0.0.0/24", 24, 24).unwrap());
+    }
+
+    #[test]
+    fn test_prefix_within_policy_invalid() {
+        assert!(matches!(prefix_within_policy("10.0.0.0/24", 28, 24), Err(SubnetError::InvalidArgument(_))));
+        let res = prefix_within_policy("10.0.0.0/24", 24, 48);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 48, max: 32 }));
+    }
+
+    #[test]
+    fn test_within_max_size() {
+        assert!(within_max_size("10.0.0.1/32", 1).unwrap());
+        assert!(within_max_size("0.0.0.0/0", 1 << 32).unwrap());
+        assert!(within_max_size("2001:db8::/112", 65_536).unwrap());
+        assert!(within_max_size("::/1", u128::MAX).unwrap());
+    }
+
+    #[test]
+    fn test_not_within_max_size() {
+        assert!(!within_max_size("10.0.0.1/32", 0).unwrap());
+        assert!(!within_max_size("0.0.0.0/0", (1 << 32) - 1).unwrap());
+        assert!(!within_max_size("2001:db8::/64", 65_536).unwrap());
+        assert!(!within_max_size("::/0", u128::MAX).unwrap());
+        assert!(within_max_size("zoop", 1).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::SubnetError;
+use crate::subnet::{Subnet, check_prefix, host_mask};
+
+
+/// The indices of the `subnets` that contain `candidate`, from the widest to
+/// the most specific. A subnet equal to `candidate` counts as containing it,
+/// and subnets of the other family are skipped.
+///
+/// # Examples
+///
+/// ### Find the ancestors of a subnet.
+///
+/// ```
+/// use subnet_utils::ancestors_of;
+///
+/// let subnets = vec!["10.1.0.0/16", "10.2.0.0/16", "10.0.0.0/8"];
+/// let res = ancestors_of("10.1.2.0/24", &subnets).unwrap();
+/// assert_eq!(res, vec![2, 0]);
+/// ```
+pub fn ancestors_of(candidate: &str, subnets: &[&str]) -> Result<Vec<usize>, SubnetError> {
+    let candidate: Subnet = candidate.parse()?;
+    let mut ancestors = Vec::new();
+    for (index, subnet) in subnets.iter().enumerate() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.contains_subnet(&candidate) {
+            ancestors.push((subnet.prefix_len(), index));
+        }
+    }
+    ancestors.sort();
+    Ok(ancestors.into_iter().map(|(_, index)| index).collect())
+}
+
+/// The indices of the `subnets` inside `parent`, ordered by network address
+/// and then prefix length. A subnet equal to `parent` counts as inside it.
+///
+/// # Examples
+///
+/// ### Find the descendants of a subnet.
+///
+/// ```
+/// use subnet_utils::descendants_of;
+///
+/// let subnets = vec!["10.1.2.0/24", "10.2.0.0/16", "10.1.0.0/24", "192.168.0.0/16"];
+/// let res = descendants_of("10.1.0.0/16", &subnets).unwrap();
+/// assert_eq!(res, vec![2, 0]);
+/// ```
+pub fn descendants_of(parent: &str, subnets: &[&str]) -> Result<Vec<usize>, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    let mut descendants = Vec::new();
+    for (index, subnet) in subnets.iter().enumerate() {
+        let subnet: Subnet = subnet.parse()?;
+        if parent.contains_subnet(&subnet) {
+            descendants.push((subnet, index));
+        }
+    }
+    descendants.sort();
+    Ok(descendants.into_iter().map(|(_, index)| index).collect())
+}
+
+/// An indented tree of `subnets` by containment, one subnet per line with
+/// each indented two spaces under the smallest subnet containing it. Siblings
+/// are in address order, and the IPv4 tree comes before the IPv6 one.
+/// Duplicates are shown once.
+///
+/// # Examples
+///
+/// ### Render a subnet tree.
+///
+/// ```
+/// use subnet_utils::render_tree;
+///
+/// let res = render_tree(&["10.1.0.0/16", "10.0.0.0/8", "10.1.2.0/24", "192.168.0.0/16"]).unwrap();
+/// assert_eq!(res, "10.0.0.0/8\n  10.1.0.0/16\n    10.1.2.0/24\n192.168.0.0/16\n");
+/// ```
+pub fn render_tree(subnets: &[&str]) -> Result<String, SubnetError> {
+    let mut parsed = subnets.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    parsed.sort();
+    parsed.dedup();
+    let mut res = String::new();
+    let mut ancestors: Vec<Subnet> = Vec::new();
+    for subnet in parsed {
+        while ancestors.last().is_some_and(|parent| !parent.contains_subnet(&subnet)) {
+            ancestors.pop();
+        }
+        res.push_str(&format!("{}{}\n", "  ".repeat(ancestors.len()), subnet));
+        ancestors.push(subnet);
+    }
+    Ok(res)
+}
+
+/// How two subnets relate, see [`subnet_contains_subnet_checked`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Containment {
+    /// The inner subnet is inside the outer one, or equal to it.
+    Contained,
+    /// Both are the same family, but the inner subnet isn't fully inside the outer one.
+    NotContained,
+    /// One subnet is IPv4 and the other IPv6.
+    FamilyMismatch,
+}
+
+/// Check if `inner` is fully inside `outer`, telling a family mismatch apart
+/// from a genuine non-containment.
+///
+/// # Examples
+///
+/// ### Check containment across families.
+///
+/// ```
+/// use subnet_utils::{Containment, subnet_contains_subnet_checked};
+///
+/// let res = subnet_contains_subnet_checked("10.0.0.0/8", "10.1.0.0/16").unwrap();
+/// assert_eq!(res, Containment::Contained);
+/// let res = subnet_contains_subnet_checked("2001:db8::/32", "10.1.0.0/16").unwrap();
+/// assert_eq!(res, Containment::FamilyMismatch);
+/// ```
+pub fn subnet_contains_subnet_checked(outer: &str, inner: &str) -> Result<Containment, SubnetError> {
+    let (outer, inner): (Subnet, Subnet) = (outer.parse()?, inner.parse()?);
+    Ok(if outer.is_ipv6() != inner.is_ipv6() {
+        Containment::FamilyMismatch
+    } else if outer.contains_subnet(&inner) {
+        Containment::Contained
+    } else {
+        Containment::NotContained
+    })
+}
+
+/// Check if `inner` is fully inside `outer`, including when they are equal.
+/// Subnets of different families are never contained, see
+/// [`subnet_contains_subnet_checked`] to tell the two cases apart.
+///
+/// # Examples
+///
+/// ### Check if a subnet contains another.
+///
+/// ```
+/// use subnet_utils::subnet_contains_subnet;
+///
+/// assert!(subnet_contains_subnet("10.0.0.0/8", "10.1.0.0/16").unwrap());
+/// ```
+pub fn subnet_contains_subnet(outer: &str, inner: &str) -> Result<bool, SubnetError> {
+    Ok(subnet_contains_subnet_checked(outer, inner)? == Containment::Contained)
+}
+
+/// The fraction (`0.0` to `1.0`) of the smaller subnet's addresses that are
+/// also in the larger one. The denominator is the size of the smaller subnet,
+/// so a subnet inside another gives `1.0` however much bigger the other is.
+/// Subnets of different families give `0.0`.
+///
+/// Two CIDR blocks are always either nested or disjoint, so the result is
+/// exactly `0.0` or `1.0`; the fraction form lets it feed similarity metrics
+/// directly.
+///
+/// # Examples
+///
+/// ### Measure the overlap of two subnets.
+///
+/// ```
+/// use subnet_utils::overlap_fraction;
+///
+/// assert_eq!(overlap_fraction("10.0.0.0/8", "10.1.0.0/16").unwrap(), 1.0);
+/// assert_eq!(overlap_fraction("10.0.0.0/16", "10.1.0.0/16").unwrap(), 0.0);
+/// ```
+pub fn overlap_fraction(a: &str, b: &str) -> Result<f64, SubnetError> {
+    let (a, b): (Subnet, Subnet) = (a.parse()?, b.parse()?);
+    let (larger, smaller) = if a.prefix_len() <= b.prefix_len() { (a, b) } else { (b, a) };
+    Ok(if larger.contains_subnet(&smaller) { 1.0 } else { 0.0 })
+}
+
+/// Which half of its immediate parent `subnet` is: `0` for the lower half and
+/// `1` for the upper half. Returns [`SubnetError::InvalidArgument`] for a
+/// `/0`, which has no parent.
+///
+/// # Examples
+///
+/// ### Find the position of a subnet within its parent.
+///
+/// ```
+/// use subnet_utils::sibling_index;
+///
+/// assert_eq!(sibling_index("10.0.0.0/25").unwrap(), 0);
+/// assert_eq!(sibling_index("10.0.0.128/25").unwrap(), 1);
+/// ```
+pub fn sibling_index(subnet: &str) -> Result<u128, SubnetError> {
+    let parsed: Subnet = subnet.parse()?;
+    match parsed.prefix_len().checked_sub(1) {
+        Some(parent_prefix) => sibling_index_within(subnet, parent_prefix),
+        None => Err(SubnetError::InvalidArgument(format!("{} has no parent", parsed))),
+    }
+}
+
+/// The position of `subnet` among the blocks of its length inside its
+/// ancestor of length `ancestor_prefix`, counting from `0` at the ancestor's
+/// network address. Returns [`SubnetError::InvalidArgument`] if
+/// `ancestor_prefix` is longer than the subnet's prefix.
+///
+/// # Examples
+///
+/// ### Find the position of a subnet within its /16.
+///
+/// ```
+/// use subnet_utils::sibling_index_within;
+///
+/// assert_eq!(sibling_index_within("10.0.3.0/24", 16).unwrap(), 3);
+/// ```
+pub fn sibling_index_within(subnet: &str, ancestor_prefix: u8) -> Result<u128, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    check_prefix(ancestor_prefix, subnet.is_ipv6())?;
+    if ancestor_prefix > subnet.prefix_len() {
+        return Err(SubnetError::InvalidArgument(format!("/{} is longer than {}", ancestor_prefix, subnet)));
+    }
+    let offset = subnet.first() & host_mask(ancestor_prefix, subnet.is_ipv6());
+    Ok(offset.checked_shr((subnet.max_prefix() - subnet.prefix_len()) as u32).unwrap_or(0))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ancestors_of() {
+        let subnets = vec!["10.1.2.0/24", "::/0", "10.1.0.0/16", "0.0.0.0/0", "10.1.2.0/25", "10.0.0.0/8"];
+        assert_eq!(ancestors_of("10.1.2.0/24", &subnets).unwrap(), vec![3, 5, 2, 0]);
+        assert_eq!(ancestors_of("2001:db8::/32", &subnets).unwrap(), vec![1]);
+    }
+
+    #[test]
+    fn test_ancestors_of_none() {
+        let subnets = vec!["10.1.2.0/25", "192.168.0.0/16"];
+        assert!(ancestors_of("10.1.2.0/24", &subnets).unwrap().is_empty());
+        assert!(ancestors_of("10.1.2.0/24", &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_descendants_of() {
+        let subnets = vec!["10.1.2.0/24", "10.1.0.0/16", "10.1.2.0/25", "10.1.1.0/24", "::/0", "10.0.0.0/8"];
+        assert_eq!(descendants_of("10.1.0.0/16", &subnets).unwrap(), vec![1, 3, 0, 2]);
+        assert_eq!(descendants_of("::/0", &subnets).unwrap(), vec![4]);
+    }
+
+    #[test]
+    fn test_descendants_of_none() {
+        let subnets = vec!["10.0.0.0/8", "192.168.0.0/16"];
+        assert!(descendants_of("10.1.0.0/16", &subnets).unwrap().is_empty());
+        assert!(descendants_of("zoop", &subnets).is_err());
+    }
+
+    #[test]
+    fn test_sibling_index() {
+        assert_eq!(sibling_index("10.0.0.128/25").unwrap(), 1);
+        assert_eq!(sibling_index("10.0.1.0/24").unwrap(), 1);
+        assert_eq!(sibling_index("10.0.2.0/24").unwrap(), 0);
+        assert_eq!(sibling_index("128.0.0.0/1").unwrap(), 1);
+        assert_eq!(sibling_index("2001:db8::1/128").unwrap(), 1);
+        assert!(matches!(sibling_index("0.0.0.0/0"), Err(SubnetError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_sibling_index_within() {
+        assert_eq!(sibling_index_within("10.0.255.0/24", 16).unwrap(), 255);
+        assert_eq!(sibling_index_within("10.0.0.192/26", 24).unwrap(), 3);
+        assert_eq!(sibling_index_within("10.0.0.0/24", 24).unwrap(), 0);
+        assert_eq!(sibling_index_within("::/0", 0).unwrap(), 0);
+        assert_eq!(sibling_index_within("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128", 0).unwrap(), u128::MAX);
+        assert!(matches!(sibling_index_within("10.0.0.0/24", 25), Err(SubnetError::InvalidArgument(_))));
+        assert!(sibling_index_within("10.0.0.0/24", 33).is_err());
+    }
+
+    #[test]
+    fn test_subnet_contains_subnet_checked() {
+        let res = subnet_contains_subnet_checked("10.0.0.0/24", "10.0.0.0/24").unwrap();
+        assert_eq!(res, Containment::Contained);
+        let res = subnet_contains_subnet_checked("10.0.0.0/24", "10.0.0.0/23").unwrap();
+        assert_eq!(res, Containment::NotContained);
+        let res = subnet_contains_subnet_checked("0.0.0.0/0", "::/0").unwrap();
+        assert_eq!(res, Containment::FamilyMismatch);
+        let res = subnet_contains_subnet_checked("::/0", "2001:db8::/32").unwrap();
+        assert_eq!(res, Containment::Contained);
+        assert!(subnet_contains_subnet_checked("10.0.0.0/8", "zoop").is_err());
+    }
+
+    #[test]
+    fn test_subnet_contains_subnet() {
+        assert!(subnet_contains_subnet("10.0.0.0/8", "10.255.255.255/32").unwrap());
+        assert!(!subnet_contains_subnet("10.0.0.0/8", "11.0.0.0/8").unwrap());
+        assert!(!subnet_contains_subnet("0.0.0.0/0", "2001:db8::/32").unwrap());
+        assert!(subnet_contains_subnet("zoop", "10.0.0.0/8").is_err());
+    }
+
+    #[test]
+    fn test_overlap_fraction() {
+        assert_eq!(overlap_fraction("10.1.2.0/24", "10.0.0.0/8").unwrap(), 1.0);
+        assert_eq!(overlap_fraction("10.0.0.0/24", "10.0.0.0/24").unwrap(), 1.0);
+        assert_eq!(overlap_fraction("::/0", "2001:db8::1/128").unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_overlap_fraction_disjoint() {
+        assert_eq!(overlap_fraction("10.0.0.0/25", "10.0.0.128/25").unwrap(), 0.0);
+        assert_eq!(overlap_fraction("0.0.0.0/0", "::/0").unwrap(), 0.0);
+        assert!(overlap_fraction("10.0.0.0/8", "zoop").is_err());
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let subnets = vec!["2001:db8:1::/48", "10.0.0.0/24", "10.0.0.0/8", "10.0.0.128/25", "10.0.1.0/24", "2001:db8::/32", "10.0.0.0/8"];
+        let expected = "10.0.0.0/8\n  10.0.0.0/24\n    10.0.0.128/25\n  10.0.1.0/24\n2001:db8::/32\n  2001:db8:1::/48\n";
+        assert_eq!(render_tree(&subnets).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_render_tree_flat() {
+        assert_eq!(render_tree(&[]).unwrap(), "");
+        assert_eq!(render_tree(&["10.0.1.0/24", "10.0.0.0/24", "::/0"]).unwrap(), "10.0.0.0/24\n10.0.1.0/24\n::/0\n");
+        assert!(render_tree(&["10.0.0.0/8", "zoop"]).is_err());
+    }
+}
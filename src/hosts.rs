@@ -0,0 +1,175 @@
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, bits_to_addr};
+
+
+/// A recommended `limit` for [`hosts_bounded`], large enough for any IPv4
+/// subnet up to a `/16` while keeping a full enumeration cheap.
+pub const DEFAULT_HOSTS_LIMIT: u128 = 65_536;
+
+/// Lazily yields every address of `subnet` from the network address up to the
+/// last address, including both.
+///
+/// The iterator is lazy but effectively endless for large subnets such as an
+/// IPv6 `/64` or any `/0`, so prefer [`hosts_bounded`] when the subnet comes
+/// from untrusted input.
+///
+/// # Examples
+///
+/// ### Iterate over the addresses of a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::hosts;
+///
+/// let res: Vec<IpAddr> = hosts("10.0.0.0/31").unwrap().collect();
+/// assert_eq!(res, vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]);
+/// ```
+pub fn hosts(subnet: &str) -> Result<impl DoubleEndedIterator<Item = IpAddr>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(AddrRange::new(subnet.first(), subnet.last(), subnet.is_ipv6()))
+}
+
+/// Like [`hosts`], but returns [`SubnetError::TooLarge`] up front if the
+/// subnet has more than `limit` addresses. [`DEFAULT_HOSTS_LIMIT`] is a
+/// sensible `limit` for most uses.
+///
+/// # Examples
+///
+/// ### Iterate over a subnet only if it is small enough.
+///
+/// ```
+/// use subnet_utils::{DEFAULT_HOSTS_LIMIT, hosts_bounded};
+///
+/// let res = hosts_bounded("192.168.182.0/24", DEFAULT_HOSTS_LIMIT).unwrap();
+/// assert_eq!(res.count(), 256);
+/// assert!(hosts_bounded("2001:db8::/64", DEFAULT_HOSTS_LIMIT).is_err());
+/// ```
+pub fn hosts_bounded(subnet: &str, limit: u128) -> Result<impl DoubleEndedIterator<Item = IpAddr>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if subnet.size() > limit {
+        return Err(SubnetError::TooLarge { size: subnet.size(), limit });
+    }
+    Ok(AddrRange::new(subnet.first(), subnet.last(), subnet.is_ipv6()))
+}
+
+/// Like [`hosts`], but yields the addresses from the last address down to the
+/// network address, for allocating from the top of a pool. It is just as lazy,
+/// so any subnet size is fine as long as only a few addresses are taken.
+///
+/// # Examples
+///
+/// ### Iterate over a subnet from the top.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::hosts_rev;
+///
+/// let res: Vec<IpAddr> = hosts_rev("10.0.0.0/31").unwrap().collect();
+/// assert_eq!(res, vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0))]);
+/// ```
+pub fn hosts_rev(subnet: &str) -> Result<impl Iterator<Item = IpAddr>, SubnetError> {
+    Ok(hosts(subnet)?.rev())
+}
+
+/// Like [`hosts_rev`], but only yields the usable host addresses, see
+/// [`describe`](crate::describe) for which addresses are usable.
+///
+/// # Examples
+///
+/// ### Iterate over the usable addresses of a subnet from the top.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::usable_hosts_rev;
+///
+/// let res: Vec<IpAddr> = usable_hosts_rev("10.0.0.0/30").unwrap().collect();
+/// assert_eq!(res, vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]);
+/// ```
+pub fn usable_hosts_rev(subnet: &str) -> Result<impl Iterator<Item = IpAddr>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let (first, last) = subnet.usable();
+    Ok(AddrRange::new(first, last, subnet.is_ipv6()).rev())
+}
+
+/// Iterator over the addresses of an inclusive integer range.
+#[derive(Clone, Debug)]
+pub(crate) struct AddrRange {
+    range: Option<(u128, u128)>,
+    v6: bool,
+}
+
+impl AddrRange {
+    pub(crate) fn new(first: u128, last: u128, v6: bool) -> Self {
+        AddrRange { range: Some((first, last)).filter(|(first, last)| first <= last), v6 }
+    }
+}
+
+impl Iterator for AddrRange {
+    type Item = IpAddr;
+
+    fn next(&mut self) -> Option<IpAddr> {
+        let (first, last) = self.range?;
+        self.range = if first < last { Some((first + 1, last)) } else { None };
+        Some(bits_to_addr(first, self.v6))
+    }
+}
+
+impl DoubleEndedIterator for AddrRange {
+    fn next_back(&mut self) -> Option<IpAddr> {
+        let (first, last) = self.range?;
+        self.range = if first < last { Some((first, last - 1)) } else { None };
+        Some(bits_to_addr(last, self.v6))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hosts() {
+        let res: Vec<IpAddr> = hosts("10.0.0.0/30").unwrap().collect();
+        assert_eq!(res.len(), 4);
+        assert_eq!(res[3], "10.0.0.3".parse::<IpAddr>().unwrap());
+        let res: Vec<IpAddr> = hosts("255.255.255.255/32").unwrap().collect();
+        assert_eq!(res, vec!["255.255.255.255".parse::<IpAddr>().unwrap()]);
+        let mut res = hosts("::/0").unwrap();
+        assert_eq!(res.next(), Some("::".parse().unwrap()));
+        assert_eq!(res.next_back(), Some("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_hosts_bounded() {
+        assert_eq!(hosts_bounded("10.0.0.0/16", DEFAULT_HOSTS_LIMIT).unwrap().count(), 65_536);
+        let res = hosts_bounded("10.0.0.0/15", DEFAULT_HOSTS_LIMIT).err();
+        assert_eq!(res, Some(SubnetError::TooLarge { size: 131_072, limit: DEFAULT_HOSTS_LIMIT }));
+        let res = hosts_bounded("::/0", DEFAULT_HOSTS_LIMIT).err();
+        assert_eq!(res, Some(SubnetError::TooLarge { size: u128::MAX, limit: DEFAULT_HOSTS_LIMIT }));
+    }
+
+    #[test]
+    fn test_hosts_rev() {
+        let res: Vec<IpAddr> = hosts_rev("10.0.0.0/30").unwrap().collect();
+        let expected: Vec<IpAddr> = ["10.0.0.3", "10.0.0.2", "10.0.0.1", "10.0.0.0"].iter().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(res, expected);
+        let mut res = hosts_rev("::/0").unwrap();
+        assert_eq!(res.next(), Some("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()));
+        assert_eq!(res.next(), Some("ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe".parse().unwrap()));
+        assert!(hosts_rev("zoop").is_err());
+    }
+
+    #[test]
+    fn test_usable_hosts_rev() {
+        let res: Vec<IpAddr> = usable_hosts_rev("10.0.0.0/29").unwrap().collect();
+        assert_eq!(res.len(), 6);
+        assert_eq!((res[0], res[5]), ("10.0.0.6".parse().unwrap(), "10.0.0.1".parse().unwrap()));
+        let res: Vec<IpAddr> = usable_hosts_rev("10.0.0.0/31").unwrap().collect();
+        assert_eq!(res, vec!["10.0.0.1".parse::<IpAddr>().unwrap(), "10.0.0.0".parse().unwrap()]);
+        let res: Vec<IpAddr> = usable_hosts_rev("2001:db8::/126").unwrap().collect();
+        let expected: Vec<IpAddr> = ["2001:db8::3", "2001:db8::2", "2001:db8::1"].iter().map(|s| s.parse().unwrap()).collect();
+        assert_eq!(res, expected);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{Family, Subnet, bits_to_addr, host_mask, mask};
+
+
+/// IPv6 capacity tables stop at this prefix, the standard LAN size.
+const IPV6_CAPACITY_PREFIX: u8 = 64;
+
+/// The derived fields of a subnet, see [`describe`].
+///
+/// With the `serde` feature enabled this implements `Serialize`, writing
+/// addresses as strings and counts as numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SubnetInfo {
+    /// The network address.
+    pub network: IpAddr,
+    /// The network mask, e.g. `255.255.255.0` for a /24.
+    pub netmask: IpAddr,
+    /// The wildcard (inverse) mask, e.g. `0.0.0.255` for a /24.
+    pub wildcard: IpAddr,
+    /// The broadcast address. IPv6 has no broadcast, so this is the last
+    /// address of the subnet.
+    pub broadcast: IpAddr,
+    /// The first usable host address.
+    pub first_usable: IpAddr,
+    /// The last usable host address.
+    pub last_usable: IpAddr,
+    /// The prefix length.
+    pub prefix_len: u8,
+    /// The number of addresses, saturating at `u128::MAX` for `::/0`.
+    pub host_count: u128,
+    /// The number of usable host addresses.
+    pub usable_count: u128,
+    /// The address family.
+    pub family: Family,
+}
+
+/// All the derived fields of `subnet` in one call, like `ipcalc`.
+///
+/// IPv4 subnets exclude the network and broadcast addresses from the usable
+/// range, and IPv6 subnets the subnet-router anycast address. Point-to-point
+/// (/31, /127) and single address (/32, /128) subnets count every address as
+/// usable.
+///
+/// # Examples
+///
+/// ### Describe a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{Family, describe};
+///
+/// let res = describe("192.168.182.1/24").unwrap();
+/// assert_eq!(res.network, IpAddr::V4(Ipv4Addr::new(192, 168, 182, 0)));
+/// assert_eq!(res.netmask, IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)));
+/// assert_eq!(res.broadcast, IpAddr::V4(Ipv4Addr::new(192, 168, 182, 255)));
+/// assert_eq!(res.usable_count, 254);
+/// assert_eq!(res.family, Family::V4);
+/// ```
+pub fn describe(subnet: &str) -> Result<SubnetInfo, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let v6 = subnet.is_ipv6();
+    let (first_usable, last_usable) = subnet.usable();
+    Ok(SubnetInfo {
+        network: subnet.network(),
+        netmask: bits_to_addr(mask(subnet.prefix_len(), v6), v6),
+        wildcard: bits_to_addr(host_mask(subnet.prefix_len(), v6), v6),
+        broadcast: bits_to_addr(subnet.last(), v6),
+        first_usable: bits_to_addr(first_usable, v6),
+        last_usable: bits_to_addr(last_usable, v6),
+        prefix_len: subnet.prefix_len(),
+        host_count: subnet.size(),
+        usable_count: last_usable - first_usable + 1,
+        family: subnet.family(),
+    })
+}
+
+/// The `(network, netmask, broadcast)` fields of `subnet`, as used by older
+/// configuration formats. IPv6 has no broadcast, so the third field is the
+/// last address of the subnet, as in [`SubnetInfo::broadcast`].
+///
+/// # Examples
+///
+/// ### Get the classic triple of a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::to_triple;
+///
+/// let (network, netmask, broadcast) = to_triple("192.168.182.1/24").unwrap();
+/// assert_eq!(network, IpAddr::V4(Ipv4Addr::new(192, 168, 182, 0)));
+/// assert_eq!(netmask, IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)));
+/// assert_eq!(broadcast, IpAddr::V4(Ipv4Addr::new(192, 168, 182, 255)));
+/// ```
+pub fn to_triple(subnet: &str) -> Result<(IpAddr, IpAddr, IpAddr), SubnetError> {
+    let info = describe(subnet)?;
+    Ok((info.network, info.netmask, info.broadcast))
+}
+
+/// Like [`to_triple`], but with the wildcard (inverse) mask in place of the
+/// netmask, as in ACL syntax: `(network, wildcard, broadcast)`.
+///
+/// # Examples
+///
+/// ### Get the wildcard triple of a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::to_wildcard_triple;
+///
+/// let (_, wildcard, _) = to_wildcard_triple("192.168.182.0/24").unwrap();
+/// assert_eq!(wildcard, IpAddr::V4(Ipv4Addr::new(0, 0, 0, 255)));
+/// ```
+pub fn to_wildcard_triple(subnet: &str) -> Result<(IpAddr, IpAddr, IpAddr), SubnetError> {
+    let info = describe(subnet)?;
+    Ok((info.network, info.wildcard, info.broadcast))
+}
+
+/// The number of subnets of each prefix length that fit in `parent`, from the
+/// parent's own prefix (one subnet) down to single addresses, like the table
+/// `ipcalc` prints.
+///
+/// IPv6 tables stop at `/64` rather than running on to `/128`, unless the
+/// parent is already longer than `/64`, in which case they run to `/128`.
+///
+/// # Examples
+///
+/// ### Get the capacity of a subnet at each prefix length.
+///
+/// ```
+/// use subnet_utils::capacity_table;
+///
+/// let res = capacity_table("10.0.0.0/24").unwrap();
+/// assert_eq!(res.len(), 9);
+/// assert_eq!(res[&24], 1);
+/// assert_eq!(res[&26], 4);
+/// assert_eq!(res[&32], 256);
+/// ```
+pub fn capacity_table(parent: &str) -> Result<BTreeMap<u8, u128>, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    let last = if parent.is_ipv6() && parent.prefix_len() <= IPV6_CAPACITY_PREFIX {
+        IPV6_CAPACITY_PREFIX
+    } else {
+        parent.max_prefix()
+    };
+    Ok((parent.prefix_len()..=last).map(|prefix| (prefix, 1u128 << (prefix - parent.prefix_len()))).collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let res = describe("10.0.0.0/30").unwrap();
+        let expected = SubnetInfo {
+            network: "10.0.0.0".parse().unwrap(),
+            netmask: "255.255.255.252".parse().unwrap(),
+            wildcard: "0.0.0.3".parse().unwrap(),
+            broadcast: "10.0.0.3".parse().unwrap(),
+            first_usable: "10.0.0.1".parse().unwrap(),
+            last_usable: "10.0.0.2".parse().unwrap(),
+            prefix_len: 30,
+            host_count: 4,
+            usable_count: 2,
+            family: Family::V4,
+        };
+        assert_eq!(res, expected);
+        let res = describe("10.0.0.0/31").unwrap();
+        assert_eq!(res.first_usable, "10.0.0.0".parse::<IpAddr>().unwrap());
+        assert_eq!(res.usable_count, 2);
+    }
+
+    #[test]
+    fn test_describe_ipv6() {
+        let res = describe("2001:db8::/64").unwrap();
+        assert_eq!(res.netmask, "ffff:ffff:ffff:ffff::".parse::<IpAddr>().unwrap());
+        assert_eq!(res.broadcast, "2001:db8::ffff:ffff:ffff:ffff".parse::<IpAddr>().unwrap());
+        assert_eq!(res.first_usable, "2001:db8::1".parse::<IpAddr>().unwrap());
+        assert_eq!(res.last_usable, res.broadcast);
+        assert_eq!(res.usable_count, (1 << 64) - 1);
+        assert_eq!(res.family, Family::V6);
+        let res = describe("::/0").unwrap();
+        assert_eq!(res.host_count, u128::MAX);
+        assert_eq!(res.usable_count, u128::MAX);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_describe_serialize() {
+        let res = serde_json::to_value(describe("10.0.0.0/24").unwrap()).unwrap();
+        assert_eq!(res["network"], "10.0.0.0");
+        assert_eq!(res["broadcast"], "10.0.0.255");
+        assert_eq!(res["prefix_len"], 24);
+        assert_eq!(res["host_count"], 256);
+        assert_eq!(res["family"], "V4");
+    }
+
+    #[test]
+    fn test_capacity_table() {
+        let res = capacity_table("10.0.0.0/30").unwrap();
+        assert_eq!(res.into_iter().collect::<Vec<_>>(), vec![(30, 1), (31, 2), (32, 4)]);
+        let res = capacity_table("0.0.0.0/0").unwrap();
+        assert_eq!(res[&32], 1 << 32);
+    }
+
+    #[test]
+    fn test_capacity_table_ipv6() {
+        let res = capacity_table("2001:db8::/48").unwrap();
+        assert_eq!(res.len(), 17);
+        assert_eq!(res[&56], 256);
+        assert_eq!(res[&64], 65_536);
+        let res = capacity_table("::/0").unwrap();
+        assert_eq!(res[&64], 1 << 64);
+        let res = capacity_table("2001:db8::/120").unwrap();
+        assert_eq!(res.keys().last(), Some(&128));
+    }
+
+    #[test]
+    fn test_to_triple() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        let res = to_triple("10.0.0.0/31").unwrap();
+        assert_eq!(res, (addr("10.0.0.0"), addr("255.255.255.254"), addr("10.0.0.1")));
+        let res = to_triple("2001:db8::1/64").unwrap();
+        assert_eq!(res, (addr("2001:db8::"), addr("ffff:ffff:ffff:ffff::"), addr("2001:db8::ffff:ffff:ffff:ffff")));
+        assert!(to_triple("zoop").is_err());
+    }
+
+    #[test]
+    fn test_to_wildcard_triple() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        let res = to_wildcard_triple("172.16.0.0/12").unwrap();
+        assert_eq!(res, (addr("172.16.0.0"), addr("0.15.255.255"), addr("172.31.255.255")));
+        let res = to_wildcard_triple("2001:db8::/32").unwrap();
+        assert_eq!(res, (addr("2001:db8::"), addr("::ffff:ffff:ffff:ffff:ffff:ffff"), addr("2001:db8:ffff:ffff:ffff:ffff:ffff:ffff")));
+    }
+}
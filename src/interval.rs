@@ -0,0 +1,189 @@
+use crate::SubnetError;
+use crate::subnet::{Family, Subnet};
+
+
+/// A static collection of subnets for repeated range overlap queries.
+///
+/// Each subnet is stored as an inclusive integer interval in an augmented
+/// interval tree, one per address family, so a query costs `O(log n + k)` for
+/// `k` results rather than a scan of every subnet.
+///
+/// # Examples
+///
+/// ### Find the subnets overlapping a range.
+///
+/// ```
+/// use std::net::Ipv4Addr;
+/// use subnet_utils::{Family, IntervalSet};
+///
+/// let set = IntervalSet::from_strs(&["10.0.0.0/24", "10.0.1.0/24", "10.0.0.0/16"]).unwrap();
+/// let start = u32::from(Ipv4Addr::new(10, 0, 0, 200)) as u128;
+/// let end = u32::from(Ipv4Addr::new(10, 0, 0, 255)) as u128;
+/// assert_eq!(set.overlapping(Family::V4, start, end), vec![0, 2]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IntervalSet {
+    v4: IntervalTree,
+    v6: IntervalTree,
+}
+
+impl IntervalSet {
+    /// Create a set by parsing each of `subnets`.
+    pub fn from_strs(subnets: &[&str]) -> Result<Self, SubnetError> {
+        subnets.iter().map(|s| s.parse()).collect()
+    }
+
+    /// The number of subnets in the set.
+    pub fn len(&self) -> usize {
+        self.v4.intervals.len() + self.v6.intervals.len()
+    }
+
+    /// Check if the set has no subnets.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The indices, in ascending order, of the subnets of `family` that overlap
+    /// the inclusive integer range `start..=end`. Indices are positions in the
+    /// input the set was built from.
+    pub fn overlapping(&self, family: Family, start: u128, end: u128) -> Vec<usize> {
+        let tree = match family {
+            Family::V4 => &self.v4,
+            Family::V6 => &self.v6,
+        };
+        let mut res = Vec::new();
+        if start <= end {
+            tree.overlapping(0, tree.intervals.len(), start, end, &mut res);
+        }
+        res.sort_unstable();
+        res
+    }
+}
+
+impl FromIterator<Subnet> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Subnet>>(iter: I) -> Self {
+        let (mut v4, mut v6) = (Vec::new(), Vec::new());
+        for (index, subnet) in iter.into_iter().enumerate() {
+            let interval = (subnet.first(), subnet.last(), index);
+            if subnet.is_ipv6() { v6.push(interval) } else { v4.push(interval) }
+        }
+        IntervalSet { v4: IntervalTree::new(v4), v6: IntervalTree::new(v6) }
+    }
+}
+
+/// Check that no subnet in `a` overlaps any subnet in `b`. Subnets of
+/// different families never overlap.
+///
+/// `b` is loaded into an [`IntervalSet`], so this costs `O((n + m) log m)`
+/// rather than a pairwise comparison, and stops at the first overlap.
+///
+/// # Examples
+///
+/// ### Check two policy lists for conflicts.
+///
+/// ```
+/// use subnet_utils::are_disjoint;
+///
+/// assert!(are_disjoint(&["10.0.0.0/24", "2001:db8::/32"], &["10.0.1.0/24"]).unwrap());
+/// assert!(!are_disjoint(&["10.0.0.0/16"], &["10.0.1.0/24"]).unwrap());
+/// ```
+pub fn are_disjoint(a: &[&str], b: &[&str]) -> Result<bool, SubnetError> {
+    let a = a.iter().map(|s| s.parse()).collect::<Result<Vec<Subnet>, _>>()?;
+    let set = IntervalSet::from_strs(b)?;
+    Ok(a.iter().all(|subnet| set.overlapping(subnet.family(), subnet.first(), subnet.last()).is_empty()))
+}
+
+/// Intervals sorted by start, read as an implicit balanced tree whose root is
+/// the middle of each range, with the largest end in each subtree alongside.
+#[derive(Clone, Debug, Default)]
+struct IntervalTree {
+    intervals: Vec<(u128, u128, usize)>,
+    max_end: Vec<u128>,
+}
+
+impl IntervalTree {
+    fn new(mut intervals: Vec<(u128, u128, usize)>) -> Self {
+        intervals.sort_unstable();
+        let mut tree = IntervalTree { max_end: vec![0; intervals.len()], intervals };
+        tree.build(0, tree.intervals.len());
+        tree
+    }
+
+    /// Fill in `max_end` for the subtree over `lo..hi`, returning its maximum.
+    fn build(&mut self, lo: usize, hi: usize) -> u128 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let max = self.intervals[mid].1.max(self.build(lo, mid)).max(self.build(mid + 1, hi));
+        self.max_end[mid] = max;
+        max
+    }
+
+    fn overlapping(&self, lo: usize, hi: usize, start: u128, end: u128, res: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] < start {
+            return;
+        }
+        self.overlapping(lo, mid, start, end, res);
+        let (first, last, index) = self.intervals[mid];
+        if first <= end {
+            if start <= last {
+                res.push(index);
+            }
+            self.overlapping(mid + 1, hi, start, end, res);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_set_overlapping() {
+        let subnets = vec!["10.0.0.0/24", "10.0.2.0/24", "10.0.0.0/8", "2001:db8::/32", "10.0.1.128/25", "11.0.0.0/8"];
+        let set = IntervalSet::from_strs(&subnets).unwrap();
+        assert_eq!(set.len(), 6);
+        let bits = |s: &str| u32::from(s.parse::<std::net::Ipv4Addr>().unwrap()) as u128;
+        assert_eq!(set.overlapping(Family::V4, bits("10.0.0.255"), bits("10.0.1.128")), vec![0, 2, 4]);
+        assert_eq!(set.overlapping(Family::V4, bits("10.255.255.255"), bits("11.0.0.0")), vec![2, 5]);
+        assert_eq!(set.overlapping(Family::V4, bits("12.0.0.0"), bits("255.255.255.255")), Vec::<usize>::new());
+        assert_eq!(set.overlapping(Family::V6, 0, u128::MAX), vec![3]);
+    }
+
+    #[test]
+    fn test_interval_set_matches_scan() {
+        let subnets: Vec<Subnet> = (0..200u32)
+            .map(|i| Subnet::from_bits((i * 7919 % 4096) as u128 * 16, 24 + (i % 9) as u8, false))
+            .collect();
+        let set: IntervalSet = subnets.iter().copied().collect();
+        for (start, end) in [(0, 10), (1000, 1000), (4000, 70_000), (65_535, 65_536), (10, 5)] {
+            let expected: Vec<usize> = (0..subnets.len())
+                .filter(|i| start <= end && subnets[*i].first() <= end && start <= subnets[*i].last())
+                .collect();
+            assert_eq!(set.overlapping(Family::V4, start, end), expected);
+        }
+    }
+
+    #[test]
+    fn test_are_disjoint() {
+        assert!(are_disjoint(&["10.0.0.0/24", "10.0.2.0/24"], &["10.0.1.0/24", "10.0.3.0/24"]).unwrap());
+        assert!(are_disjoint(&["10.0.0.0/8"], &["::/0"]).unwrap());
+        assert!(are_disjoint(&[], &["10.0.0.0/8"]).unwrap());
+        assert!(are_disjoint(&["10.0.0.0/8"], &[]).unwrap());
+    }
+
+    #[test]
+    fn test_not_are_disjoint() {
+        assert!(!are_disjoint(&["10.0.0.0/24", "10.0.1.128/25"], &["10.0.1.0/24"]).unwrap());
+        assert!(!are_disjoint(&["2001:db8::/48"], &["10.0.0.0/8", "2001:db8::/32"]).unwrap());
+        assert!(!are_disjoint(&["10.0.0.5/32"], &["0.0.0.0/0"]).unwrap());
+        assert!(are_disjoint(&["zoop"], &["10.0.0.0/8"]).is_err());
+        assert!(are_disjoint(&["10.0.0.0/8"], &["zoop"]).is_err());
+    }
+}
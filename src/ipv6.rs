@@ -0,0 +1,210 @@
+//! IPv6-specific helpers.
+
+use std::net::{IpAddr, Ipv6Addr};
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, Subnets, check_prefix};
+
+
+/// Combines the network prefix of `subnet` with the modified EUI-64 interface
+/// identifier derived from `mac` (RFC 4291, appendix A).
+///
+/// The subnet must be IPv6 with a prefix of at most `/64`.
+///
+/// # Examples
+///
+/// ### Build a SLAAC address from a subnet and a MAC address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv6Addr};
+/// use subnet_utils::ipv6::eui64_address;
+///
+/// let res = eui64_address("2001:db8::/64", [0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e]).unwrap();
+/// assert_eq!(res, "2001:db8::21a:2bff:fe3c:4d5e".parse::<IpAddr>().unwrap());
+/// ```
+pub fn eui64_address(subnet: &str, mac: [u8; 6]) -> Result<IpAddr, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if !subnet.is_ipv6() {
+        return Err(SubnetError::FamilyMismatch);
+    }
+    if subnet.prefix_len() > 64 {
+        return Err(SubnetError::InvalidPrefix { prefix: subnet.prefix_len(), max: 64 });
+    }
+    let iid = u64::from_be_bytes([mac[0] ^ 0x02, mac[1], mac[2], 0xff, 0xfe, mac[3], mac[4], mac[5]]);
+    Ok(IpAddr::V6(Ipv6Addr::from(subnet.first() | iid as u128)))
+}
+
+/// Checks if `addr` is the subnet-router anycast address of `subnet`, i.e. the
+/// subnet prefix with an all-zeros interface identifier (RFC 4291).
+///
+/// IPv4 addresses and subnets always return `false`, as do `/127` and `/128`
+/// subnets, which have no subnet-router anycast address (RFC 6164).
+///
+/// # Examples
+///
+/// ### Check if an address is the subnet-router anycast address.
+///
+/// ```
+/// use std::net::IpAddr;
+/// use subnet_utils::ipv6::is_subnet_router_anycast;
+///
+/// let res = is_subnet_router_anycast(&"2001:db8::".parse::<IpAddr>().unwrap(), "2001:db8::/64").unwrap();
+/// assert!(res);
+/// ```
+pub fn is_subnet_router_anycast(addr: &IpAddr, subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(subnet.is_ipv6() && subnet.prefix_len() < 127 && *addr == subnet.network())
+}
+
+
+/// Lazily yields each `delegated_prefix`-length block within the IPv6
+/// `parent`, in address order, for handing out prefix delegations.
+///
+/// Blocks are produced on demand, so splitting a `/32` into `/64`s is fine as
+/// long as the caller only takes what it needs. `delegated_prefix` must be
+/// longer than the parent's prefix.
+///
+/// # Examples
+///
+/// ### Delegate /56s from a /48.
+///
+/// ```
+/// use subnet_utils::ipv6::delegate_prefixes;
+///
+/// let mut res = delegate_prefixes("2001:db8:1::/48", 56).unwrap();
+/// assert_eq!(res.next().as_deref(), Some("2001:db8:1::/56"));
+/// assert_eq!(res.next().as_deref(), Some("2001:db8:1:100::/56"));
+/// ```
+pub fn delegate_prefixes(parent: &str, delegated_prefix: u8) -> Result<impl Iterator<Item = String>, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    if !parent.is_ipv6() {
+        return Err(SubnetError::FamilyMismatch);
+    }
+    check_prefix(delegated_prefix, true)?;
+    if delegated_prefix <= parent.prefix_len() {
+        return Err(SubnetError::InvalidArgument(format!(
+            "delegated prefix /{} is not longer than {}",
+            delegated_prefix, parent
+        )));
+    }
+    Ok(Delegations(parent.subnets(delegated_prefix)))
+}
+
+/// The blocks of [`delegate_prefixes`], formatted only as they're yielded so
+/// blocks skipped with `nth` cost nothing.
+struct Delegations(Subnets);
+
+impl Iterator for Delegations {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.0.next().map(|subnet| subnet.to_string())
+    }
+
+    fn nth(&mut self, n: usize) -> Option<String> {
+        self.0.nth(n).map(|subnet| subnet.to_string())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+
+/// Checks if two IPv6 addresses share the same `/64`, i.e. the same link
+/// prefix under standard SLAAC addressing.
+///
+/// # Examples
+///
+/// ### Check if two addresses are on the same /64.
+///
+/// ```
+/// use std::net::Ipv6Addr;
+/// use subnet_utils::ipv6::same_ipv6_link;
+///
+/// let a: Ipv6Addr = "2001:db8::1".parse().unwrap();
+/// let b: Ipv6Addr = "2001:db8::ffff:1".parse().unwrap();
+/// assert!(same_ipv6_link(&a, &b));
+/// ```
+pub fn same_ipv6_link(a: &Ipv6Addr, b: &Ipv6Addr) -> bool {
+    u128::from(*a) >> 64 == u128::from(*b) >> 64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eui64_address() {
+        let res = eui64_address("fe80::/64", [0x52, 0x54, 0x00, 0x12, 0x34, 0x56]).unwrap();
+        assert_eq!(res, "fe80::5054:ff:fe12:3456".parse::<IpAddr>().unwrap());
+        let res = eui64_address("2001:db8:1::/48", [0x02, 0x00, 0x00, 0x00, 0x00, 0x01]).unwrap();
+        assert_eq!(res, "2001:db8:1::ff:fe00:1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_eui64_address_invalid() {
+        let mac = [0x52, 0x54, 0x00, 0x12, 0x34, 0x56];
+        assert_eq!(eui64_address("192.168.182.0/24", mac), Err(SubnetError::FamilyMismatch));
+        assert_eq!(eui64_address("2001:db8::/96", mac), Err(SubnetError::InvalidPrefix { prefix: 96, max: 64 }));
+    }
+
+    #[test]
+    fn test_is_subnet_router_anycast() {
+        let addr: IpAddr = "2001:db8::".parse().unwrap();
+        assert!(is_subnet_router_anycast(&addr, "2001:db8::/64").unwrap());
+        assert!(is_subnet_router_anycast(&addr, "2001:db8::1/64").unwrap());
+    }
+
+    #[test]
+    fn test_is_not_subnet_router_anycast() {
+        let addr: IpAddr = "2001:db8::1".parse().unwrap();
+        assert!(!is_subnet_router_anycast(&addr, "2001:db8::/64").unwrap());
+        let addr: IpAddr = "2001:db8::".parse().unwrap();
+        assert!(!is_subnet_router_anycast(&addr, "2001:db8::/127").unwrap());
+        let addr: IpAddr = "192.168.182.0".parse().unwrap();
+        assert!(!is_subnet_router_anycast(&addr, "192.168.182.0/24").unwrap());
+    }
+
+    #[test]
+    fn test_delegate_prefixes() {
+        let res: Vec<String> = delegate_prefixes("2001:db8:1::/48", 56).unwrap().collect();
+        assert_eq!(res.len(), 256);
+        assert_eq!(res[255], "2001:db8:1:ff00::/56");
+        let res = delegate_prefixes("2001:db8::/32", 64).unwrap().nth(65_537);
+        assert_eq!(res.as_deref(), Some("2001:db8:1:1::/64"));
+        let res: Vec<String> = delegate_prefixes("::/0", 1).unwrap().collect();
+        assert_eq!(res, vec!["::/1", "8000::/1"]);
+    }
+
+    #[test]
+    fn test_delegate_prefixes_skips_lazily() {
+        let mut res = delegate_prefixes("2001:db8::/32", 128).unwrap();
+        assert_eq!(res.nth(1 << 40).as_deref(), Some("2001:db8::100:0:0/128"));
+        assert_eq!(res.next().as_deref(), Some("2001:db8::100:0:1/128"));
+        assert_eq!(delegate_prefixes("2001:db8:1::/48", 56).unwrap().size_hint(), (256, Some(256)));
+        assert_eq!(delegate_prefixes("2001:db8:1::/48", 56).unwrap().nth(256), None);
+    }
+
+    #[test]
+    fn test_delegate_prefixes_invalid() {
+        assert_eq!(delegate_prefixes("10.0.0.0/8", 16).err(), Some(SubnetError::FamilyMismatch));
+        assert!(matches!(delegate_prefixes("2001:db8::/48", 48).err(), Some(SubnetError::InvalidArgument(_))));
+        assert!(matches!(delegate_prefixes("2001:db8::/48", 129).err(), Some(SubnetError::InvalidPrefix { .. })));
+    }
+
+    #[test]
+    fn test_same_ipv6_link() {
+        let a: Ipv6Addr = "2001:db8:0:1::1".parse().unwrap();
+        assert!(same_ipv6_link(&a, &"2001:db8:0:1:ffff:ffff:ffff:ffff".parse().unwrap()));
+        assert!(same_ipv6_link(&a, &a));
+    }
+
+    #[test]
+    fn test_not_same_ipv6_link() {
+        let a: Ipv6Addr = "2001:db8:0:1::1".parse().unwrap();
+        assert!(!same_ipv6_link(&a, &"2001:db8:0:2::1".parse().unwrap()));
+        assert!(!same_ipv6_link(&"fe80::1".parse().unwrap(), &"fe81::1".parse().unwrap()));
+    }
+}
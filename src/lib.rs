@@ -10,6 +10,8 @@
 //! [dependencies]
 //! subnet-utils = "0.1"
 //! ```
+//!
+//! Enable the `serde` feature to serialize [`SubnetInfo`] summaries, e.g. as JSON.
 //! 
 //! ## Examples
 //!
@@ -56,11 +58,80 @@
 //! let res = any_addr_in_any_subnet(&addrs, &subnets).unwrap();
 //! assert!(res);
 //! ```
+//!
+//! ### Check if an address is allowed by allow/deny subnets.
+//!
+//! ```
+//! use std::net::{IpAddr, Ipv4Addr};
+//! use subnet_utils::addr_allowed;
+//!
+//! let allow = vec!["192.168.0.0/16"];
+//! let deny = vec!["192.168.182.0/24"];
+//! let res = addr_allowed(&IpAddr::V4(Ipv4Addr::new(192, 168, 181, 1)), &allow, &deny).unwrap();
+//! assert!(res);
+//! ```
+
+
+mod aggregate;
+mod cache;
+mod calc;
+mod detail;
+mod error;
+mod filter;
+mod hierarchy;
+mod hosts;
+mod info;
+mod interval;
+mod like;
+mod parse;
+mod pool;
+mod range;
+mod set;
+mod special;
+mod split;
+mod subnet;
 
+pub mod ipv6;
 
-use std::error::Error;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-use netaddr2::{Contains, Error as NetError, NetAddr};
+use std::collections::HashSet;
+use std::hash::BuildHasher;
+use std::net::{IpAddr, SocketAddr};
+use netaddr2::{Contains, NetAddr};
+
+use parse::parse_netaddr;
+
+pub use aggregate::{
+    aggregate_lossy, aggregate_sorted_stream, collapse_hosts, complement, dedupe_keep_specific, distinct_address_count,
+    is_aggregated, merge_into, space_diff,
+};
+pub use cache::CachingMatcher;
+pub use calc::{
+    Alignment, BoundaryKind, GatewayPosition, address_distance, aggregation_key, alignment_info, block_size,
+    broadcast_for, conventional_gateway, host_bits_to_prefix, is_boundary_address, is_conventional_gateway,
+    is_host_route, is_last_address, is_point_to_point, mask_addr, mask_bits, next_subnet, prefix_for_hosts,
+    prefix_to_host_bits, prev_subnet, rollup, same_broadcast_domain, subnet_hash,
+};
+pub use detail::{
+    MatchDetail, MatchSummary, explain_mismatch, first_matching_subnet_str, match_depth, match_detail, match_matrix,
+    nearest_enclosing, summarize_addrs,
+};
+pub use error::SubnetError;
+pub use filter::{PrefixFilter, prefix_within_policy, within_max_size};
+pub use hierarchy::{
+    Containment, ancestors_of, descendants_of, overlap_fraction, render_tree, sibling_index, sibling_index_within,
+    subnet_contains_subnet, subnet_contains_subnet_checked,
+};
+pub use hosts::{DEFAULT_HOSTS_LIMIT, hosts, hosts_bounded, hosts_rev, usable_hosts_rev};
+pub use info::{SubnetInfo, capacity_table, describe, to_triple, to_wildcard_triple};
+pub use interval::{IntervalSet, are_disjoint};
+pub use like::SubnetLike;
+pub use parse::{align, canonicalize_all, parse_addr_or_subnet, parse_lenient, parse_v4, parse_v6};
+pub use pool::{first_free_subnet, largest_free_prefix, utilization, utilization_from_count};
+pub use range::{BOUNDARIES_LIMIT, align_range, blocks_needed, boundaries, midpoint_subnet, range_overlaps_any};
+pub use set::SubnetSet;
+pub use special::{crosses_classful_boundary, is_documentation_subnet, is_globally_routable, is_reserved};
+pub use split::{STANDARD_BLOCKS_LIMIT, child_subnets_range, divide_into, subnet_at_depth, to_standard_blocks};
+pub use subnet::{Family, ParsedSubnet, Subnet};
 
 
 /// # Examples
@@ -74,19 +145,100 @@ use netaddr2::{Contains, Error as NetError, NetAddr};
 /// let res = addr_in_subnet(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), "192.168.182.0/24").unwrap();
 /// assert!(res);
 /// ```
-pub fn addr_in_subnet(addr: &IpAddr, subnet: &str) -> Result<bool, Box<dyn Error>> {
-    match subnet.parse::<NetAddr>() {
+pub fn addr_in_subnet(addr: &IpAddr, subnet: &str) -> Result<bool, SubnetError> {
+    match parse_netaddr(subnet) {
         Ok(NetAddr::V4(subnet4)) => {
-            Ok(addr.is_ipv4() && subnet4.contains(addr.into()))
+            Ok(addr.is_ipv4() && subnet4.contains(addr))
         }
         Ok(NetAddr::V6(subnet6)) => {
-            Ok(addr.is_ipv6() && subnet6.contains(addr.into()))
+            Ok(addr.is_ipv6() && subnet6.contains(addr))
         }
-        Err(NetError::ParseError(e)) => Err(e.into()),
+        Err(e) => Err(e),
     }
 }
 
 
+/// Like [`addr_in_subnet`], but parses the subnet only on first use and does
+/// not allocate on later checks. An unparseable subnet never matches; use
+/// [`ParsedSubnet::subnet`] to get the parse error.
+///
+/// # Examples
+///
+/// ### Check if a cached subnet contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{ParsedSubnet, addr_in_subnet_cached};
+///
+/// let mut subnet = ParsedSubnet::new("192.168.182.0/24");
+/// let res = addr_in_subnet_cached(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &mut subnet);
+/// assert!(res);
+/// ```
+pub fn addr_in_subnet_cached(addr: &IpAddr, subnet: &mut ParsedSubnet) -> bool {
+    subnet.subnet().is_ok_and(|subnet| subnet.contains(addr))
+}
+
+
+/// Like [`addr_in_any_subnet`], but unparseable subnets are treated as not
+/// matching instead of returning an error.
+///
+/// This swallows parse errors by design, for best-effort scans of dirty data
+/// such as scraped logs where one malformed entry shouldn't abort the scan.
+/// Use [`addr_in_any_subnet`] when bad input should be reported.
+///
+/// # Examples
+///
+/// ### Check a list containing malformed subnets.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::addr_in_any_subnet_lenient;
+///
+/// let subnets = vec!["zoop", "192.168.182.0/33", "192.168.182.0/24"];
+/// let res = addr_in_any_subnet_lenient(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets);
+/// assert!(res);
+/// ```
+pub fn addr_in_any_subnet_lenient(addr: &IpAddr, subnets: &[&str]) -> bool {
+    subnets.iter().any(|subnet| subnet.contains_addr(addr))
+}
+
+/// Like [`addr_in_any_subnet`], but checks the subnets of a `HashSet`, such as
+/// a set of canonical subnet strings. It accepts `HashSet<String>`,
+/// `HashSet<&str>` or a set of any other [`SubnetLike`] type, with any hasher.
+///
+/// This is a scan like [`addr_in_any_subnet`], not a hash lookup. The set's
+/// iteration order is arbitrary, so with several bad entries, which parse
+/// error is returned is unspecified.
+///
+/// # Examples
+///
+/// ### Check if a set of subnets contains an address.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::addr_in_subnet_set;
+///
+/// let subnets: HashSet<String> = ["192.168.181.0/24".to_string(), "192.168.182.0/24".to_string()].into();
+/// let res = addr_in_subnet_set(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
+/// assert!(res);
+/// ```
+pub fn addr_in_subnet_set<T: SubnetLike, S: BuildHasher>(addr: &IpAddr, subnets: &HashSet<T, S>) -> Result<bool, SubnetError> {
+    for subnet in subnets.iter() {
+        if subnet.try_contains_addr(addr)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Check if any of the subnets contains `addr`. Returns an error for the first
+/// malformed subnet it reaches, whatever the family of `addr`.
+///
+/// Well-formed `address/prefix` strings of the other family are skipped without
+/// being fully parsed, which speeds up lists that are mostly of the other
+/// family. See [`addr_in_any_subnet_like`] for other [`SubnetLike`] subnets.
+///
 /// # Examples
 ///
 /// ### Check if any subnet contains an address.
@@ -99,25 +251,39 @@ pub fn addr_in_subnet(addr: &IpAddr, subnet: &str) -> Result<bool, Box<dyn Error
 /// let res = addr_in_any_subnet(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
 /// assert!(res);
 /// ```
-pub fn addr_in_any_subnet(addr: &IpAddr, subnets: &[&str]) -> Result<bool, Box<dyn Error>> {
+pub fn addr_in_any_subnet(addr: &IpAddr, subnets: &[&str]) -> Result<bool, SubnetError> {
+    addr_in_any_subnet_like(addr, subnets)
+}
+
+/// Like [`addr_in_any_subnet`], but works with any [`SubnetLike`] subnets.
+///
+/// # Examples
+///
+/// ### Check if any parsed subnet contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{Subnet, addr_in_any_subnet_like};
+///
+/// let subnets: Vec<Subnet> = vec!["192.168.181.0/24".parse().unwrap(), "192.168.182.0/24".parse().unwrap()];
+/// let res = addr_in_any_subnet_like(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
+/// assert!(res);
+/// ```
+pub fn addr_in_any_subnet_like<T: SubnetLike>(addr: &IpAddr, subnets: &[T]) -> Result<bool, SubnetError> {
+    let family = if addr.is_ipv6() { Family::V6 } else { Family::V4 };
     for subnet in subnets.iter() {
-        match subnet.parse::<NetAddr>() {
-            Ok(NetAddr::V4(subnet4)) => {
-                if addr.is_ipv4() && subnet4.contains(addr.into()) {
-                    return Ok(true)
-                }
-            }
-            Ok(NetAddr::V6(subnet6)) => {
-                if addr.is_ipv6() && subnet6.contains(addr.into()) {
-                    return Ok(true)
-                }
-            }
-            Err(NetError::ParseError(e)) => return Err(e.into()),
+        if subnet.family_hint().is_some_and(|hint| hint != family) {
+            continue;
+        }
+        if subnet.try_contains_addr(addr)? {
+            return Ok(true);
         }
     }
     Ok(false)
 }
 
+/// See [`addr_in_all_subnets_like`] for other [`SubnetLike`] subnets.
+///
 /// # Examples
 /// 
 /// ### Check if all subnets contain an address.
@@ -130,9 +296,27 @@ pub fn addr_in_any_subnet(addr: &IpAddr, subnets: &[&str]) -> Result<bool, Box<d
 /// let res = addr_in_all_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
 /// assert!(res);
 /// ```
-pub fn addr_in_all_subnets(addr: &IpAddr, subnets: &[&str]) -> Result<bool, Box<dyn Error>> {
+pub fn addr_in_all_subnets(addr: &IpAddr, subnets: &[&str]) -> Result<bool, SubnetError> {
+    addr_in_all_subnets_like(addr, subnets)
+}
+
+/// Like [`addr_in_all_subnets`], but works with any [`SubnetLike`] subnets.
+///
+/// # Examples
+///
+/// ### Check if all parsed subnets contain an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{Subnet, addr_in_all_subnets_like};
+///
+/// let subnets: Vec<Subnet> = vec!["192.168.182.0/24".parse().unwrap(), "192.168.182.1/32".parse().unwrap()];
+/// let res = addr_in_all_subnets_like(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
+/// assert!(res);
+/// ```
+pub fn addr_in_all_subnets_like<T: SubnetLike>(addr: &IpAddr, subnets: &[T]) -> Result<bool, SubnetError> {
     for subnet in subnets.iter() {
-        if let Ok(false) = addr_in_subnet(addr, &subnet) {
+        if let Ok(false) = subnet.try_contains_addr(addr) {
             return Ok(false);
         }
     }
@@ -152,33 +336,255 @@ pub fn addr_in_all_subnets(addr: &IpAddr, subnets: &[&str]) -> Result<bool, Box<
 /// let res = any_addr_in_any_subnet(&addrs, &subnets).unwrap();
 /// assert!(res);
 /// ```
-pub fn any_addr_in_any_subnet(addrs: &Vec<IpAddr>, subnets: &[&str]) -> Result<bool, Box<dyn Error>> {
+pub fn any_addr_in_any_subnet(addrs: &[IpAddr], subnets: &[&str]) -> Result<bool, SubnetError> {
     for subnet in subnets.iter() {
-        match subnet.parse::<NetAddr>() {
+        match parse_netaddr(subnet) {
             Ok(NetAddr::V4(subnet4)) => {
                 for addr in addrs.iter() {
-                    if addr.is_ipv4() && subnet4.contains(addr.into()) {
+                    if addr.is_ipv4() && subnet4.contains(addr) {
                         return Ok(true)
                     }
                 }
             }
             Ok(NetAddr::V6(subnet6)) => {
                 for addr in addrs.iter() {
-                    if addr.is_ipv6() && subnet6.contains(addr.into()) {
+                    if addr.is_ipv6() && subnet6.contains(addr) {
                         return Ok(true)
                     }
                 }
             }
-            Err(NetError::ParseError(e)) => return Err(e.into()),
+            Err(e) => return Err(e),
         }
     }
     Ok(false)
 }
 
 
+/// Every address must be in the subnet. An empty address list is vacuously
+/// contained and returns `true`.
+///
+/// # Examples
+///
+/// ### Check if a subnet contains all addresses.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::all_addrs_in_subnet;
+///
+/// let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 182, 2))];
+/// let res = all_addrs_in_subnet(&addrs, "192.168.182.0/24").unwrap();
+/// assert!(res);
+/// ```
+pub fn all_addrs_in_subnet(addrs: &[IpAddr], subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(addrs.iter().all(|addr| subnet.contains(addr)))
+}
+
+
+/// # Examples
+///
+/// ### Check if any subnet contains a subnet.
+///
+/// ```
+/// use subnet_utils::subnet_in_any_subnet;
+///
+/// let subnets = vec!["192.168.181.0/24", "192.168.182.0/23"];
+/// let res = subnet_in_any_subnet("192.168.182.128/25", &subnets).unwrap();
+/// assert!(res);
+/// ```
+pub fn subnet_in_any_subnet(candidate: &str, subnets: &[&str]) -> Result<bool, SubnetError> {
+    let candidate: Subnet = candidate.parse()?;
+    for subnet in subnets.iter() {
+        if subnet.parse::<Subnet>()?.contains_subnet(&candidate) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Like [`addr_in_any_subnet`], but binary searches a slice of subnets that is
+/// already sorted in [`Subnet`] order: IPv4 before IPv6, then by network
+/// address and prefix length, as produced by `sort()`.
+///
+/// The slice must be sorted; if it isn't, the result is unspecified.
+///
+/// # Examples
+///
+/// ### Check if a sorted slice of subnets contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{Subnet, addr_in_sorted_subnets};
+///
+/// let mut subnets: Vec<Subnet> = vec!["192.168.182.0/24".parse().unwrap(), "10.0.0.0/8".parse().unwrap()];
+/// subnets.sort();
+/// let res = addr_in_sorted_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets);
+/// assert!(res);
+/// ```
+pub fn addr_in_sorted_subnets(addr: &IpAddr, sorted: &[Subnet]) -> bool {
+    let (bits, v6) = (subnet::addr_to_bits(addr), addr.is_ipv6());
+    (0..=subnet::max_prefix(v6)).any(|prefix| sorted.binary_search(&Subnet::from_bits(bits, prefix, v6)).is_ok())
+}
+
+/// The index of the first subnet that contains both `a` and `b`, or `None` if
+/// no subnet does.
+///
+/// # Examples
+///
+/// ### Check if two addresses share a subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::share_a_subnet;
+///
+/// let a = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1));
+/// let b = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 254));
+/// let res = share_a_subnet(&a, &b, &["192.168.181.0/24", "192.168.182.0/24"]).unwrap();
+/// assert_eq!(res, Some(1));
+/// ```
+pub fn share_a_subnet(a: &IpAddr, b: &IpAddr, subnets: &[&str]) -> Result<Option<usize>, SubnetError> {
+    for (index, subnet) in subnets.iter().enumerate() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.contains(a) && subnet.contains(b) {
+            return Ok(Some(index));
+        }
+    }
+    Ok(None)
+}
+
+/// Like [`addr_in_subnet`], but checks the IP of a socket address, ignoring the port.
+///
+/// # Examples
+///
+/// ### Check if subnet contains a socket address.
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use subnet_utils::sockaddr_in_subnet;
+///
+/// let addr: SocketAddr = "192.168.182.1:443".parse().unwrap();
+/// let res = sockaddr_in_subnet(&addr, "192.168.182.0/24").unwrap();
+/// assert!(res);
+/// ```
+pub fn sockaddr_in_subnet(addr: &SocketAddr, subnet: &str) -> Result<bool, SubnetError> {
+    addr_in_subnet(&addr.ip(), subnet)
+}
+
+/// Like [`addr_in_any_subnet`], but checks the IP of a socket address, ignoring the port.
+///
+/// # Examples
+///
+/// ### Check if any subnet contains a socket address.
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use subnet_utils::sockaddr_in_any_subnet;
+///
+/// let addr: SocketAddr = "[2001:db8::1]:443".parse().unwrap();
+/// let res = sockaddr_in_any_subnet(&addr, &["192.168.182.0/24", "2001:db8::/32"]).unwrap();
+/// assert!(res);
+/// ```
+pub fn sockaddr_in_any_subnet(addr: &SocketAddr, subnets: &[&str]) -> Result<bool, SubnetError> {
+    addr_in_any_subnet(&addr.ip(), subnets)
+}
+
+/// The negation of [`addr_in_any_subnet`]: `true` when no subnet contains `addr`.
+///
+/// # Examples
+///
+/// ### Check if an address is outside all subnets.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::addr_outside_all_subnets;
+///
+/// let blocklist = vec!["192.168.181.0/24", "192.168.182.0/24"];
+/// let res = addr_outside_all_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1)), &blocklist).unwrap();
+/// assert!(res);
+/// ```
+pub fn addr_outside_all_subnets<T: SubnetLike>(addr: &IpAddr, subnets: &[T]) -> Result<bool, SubnetError> {
+    Ok(!addr_in_any_subnet_like(addr, subnets)?)
+}
+
+/// `true` when at least one of `addrs` is outside all of the subnets.
+///
+/// # Examples
+///
+/// ### Check if any address is outside all subnets.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::any_addr_outside_all_subnets;
+///
+/// let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1))];
+/// let res = any_addr_outside_all_subnets(&addrs, &["192.168.182.0/24"]).unwrap();
+/// assert!(res);
+/// ```
+pub fn any_addr_outside_all_subnets<T: SubnetLike>(addrs: &[IpAddr], subnets: &[T]) -> Result<bool, SubnetError> {
+    for addr in addrs.iter() {
+        if addr_outside_all_subnets(addr, subnets)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Check if `addr` is a usable host address in at least one of the subnets,
+/// i.e. contained in it and neither its network nor broadcast address. Each
+/// subnet follows the same rules as [`describe`]: every address of an IPv4
+/// `/31` or `/32` is usable, and IPv6 subnets only exclude the network address.
+///
+/// # Examples
+///
+/// ### Check if an address is assignable in any pool.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::is_usable_in_any;
+///
+/// let pools = vec!["192.168.182.0/24", "192.168.182.254/31"];
+/// assert!(!is_usable_in_any(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 0)), &pools).unwrap());
+/// assert!(is_usable_in_any(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 255)), &pools).unwrap());
+/// ```
+pub fn is_usable_in_any(addr: &IpAddr, subnets: &[&str]) -> Result<bool, SubnetError> {
+    let bits = subnet::addr_to_bits(addr);
+    for subnet in subnets.iter() {
+        let subnet: Subnet = subnet.parse()?;
+        let (first, last) = subnet.usable();
+        if subnet.contains(addr) && first <= bits && bits <= last {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Deny subnets take precedence: an address that matches both lists is not allowed.
+///
+/// # Examples
+///
+/// ### Check if an address is allowed by allow/deny subnets.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::addr_allowed;
+///
+/// let allow = vec!["192.168.0.0/16"];
+/// let deny = vec!["192.168.182.0/24"];
+/// let res = addr_allowed(&IpAddr::V4(Ipv4Addr::new(192, 168, 181, 1)), &allow, &deny).unwrap();
+/// assert!(res);
+/// ```
+pub fn addr_allowed(addr: &IpAddr, allow: &[&str], deny: &[&str]) -> Result<bool, SubnetError> {
+    if addr_in_any_subnet(addr, deny)? {
+        return Ok(false);
+    }
+    addr_in_any_subnet(addr, allow)
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::net::Ipv4Addr;
 
     #[test]
     fn test_addr_in_subnet() {
@@ -220,6 +626,13 @@ mod tests {
         assert!(!res);
     }
 
+    #[test]
+    fn test_addr_in_all_subnets_skips_invalid() {
+        assert_eq!(addr_in_all_subnets(&"2001:db8::1".parse().unwrap(), &["zoop"]), Ok(true));
+        assert_eq!(addr_in_all_subnets(&"2001:db8::1".parse().unwrap(), &["2001:db8::/32", "zoop::"]), Ok(true));
+        assert_eq!(addr_in_all_subnets(&"2001:db8::1".parse().unwrap(), &["zoop", "10.0.0.0/8"]), Ok(false));
+    }
+
     #[test]
     fn test_any_addr_in_any_subnet() {
         let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 182, 2))];
@@ -235,5 +648,244 @@ mod tests {
         let res = any_addr_in_any_subnet(&addrs, &subnets).unwrap();
         assert!(!res);
     }
-}
 
+    #[test]
+    fn test_addr_allowed() {
+        let allow = vec!["192.168.0.0/16"];
+        let deny = vec!["192.168.182.0/24"];
+        let res = addr_allowed(&IpAddr::V4(Ipv4Addr::new(192, 168, 181, 1)), &allow, &deny).unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn test_addr_not_allowed() {
+        let allow = vec!["192.168.0.0/16"];
+        let deny = vec!["192.168.182.0/24"];
+        let res = addr_allowed(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &allow, &deny).unwrap();
+        assert!(!res);
+        let res = addr_allowed(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), &allow, &deny).unwrap();
+        assert!(!res);
+    }
+
+    #[test]
+    fn test_addr_in_subnet_messy_input() {
+        let res = addr_in_subnet(&"2001:db8::1".parse().unwrap(), " 2001:DB8::/32 ").unwrap();
+        assert!(res);
+        let subnets = vec!["192.168.181.0/24 ", "\t192.168.182.0/24"];
+        let res = addr_in_any_subnet(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap();
+        assert!(res);
+    }
+
+    #[test]
+    fn test_addr_in_subnet_non_contiguous_mask() {
+        let addr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5));
+        let expected = Err(SubnetError::NonContiguousMask { mask: "255.255.0.255".to_string() });
+        assert_eq!(addr_in_subnet(&addr, "10.0.0.0/255.255.0.255"), expected);
+        assert_eq!(addr_in_subnet(&addr, "10.0.0.0 255.255.0.255"), expected);
+        assert_eq!(addr_in_any_subnet(&addr, &["10.0.0.0/8", "10.0.0.0/255.255.0.255"]), Ok(true));
+        assert_eq!(addr_in_any_subnet(&addr, &["10.0.0.0/255.255.0.255", "10.0.0.0/8"]), expected);
+        assert!(addr_in_subnet(&addr, "10.0.0.0/255.255.0.0").unwrap());
+    }
+
+    #[test]
+    fn test_addr_in_subnet_cached() {
+        let mut subnet = ParsedSubnet::new("192.168.182.0/24");
+        assert!(addr_in_subnet_cached(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &mut subnet));
+        assert!(!addr_in_subnet_cached(&IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1)), &mut subnet));
+    }
+
+    #[test]
+    fn test_addr_in_invalid_subnet_cached() {
+        let mut subnet = ParsedSubnet::new("zoop");
+        assert!(!addr_in_subnet_cached(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &mut subnet));
+    }
+
+    #[test]
+    fn test_subnet_in_any_subnet() {
+        let subnets = vec!["192.168.181.0/24", "192.168.182.0/23"];
+        assert!(subnet_in_any_subnet("192.168.182.128/25", &subnets).unwrap());
+        assert!(subnet_in_any_subnet("192.168.181.0/24", &subnets).unwrap());
+    }
+
+    #[test]
+    fn test_subnet_not_in_any_subnet() {
+        let subnets = vec!["192.168.181.0/24", "192.168.182.0/24"];
+        assert!(!subnet_in_any_subnet("192.168.182.0/23", &subnets).unwrap());
+        assert!(!subnet_in_any_subnet("::/64", &["0.0.0.0/0"]).unwrap());
+    }
+
+    #[test]
+    fn test_all_addrs_in_subnet() {
+        let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 182, 2))];
+        assert!(all_addrs_in_subnet(&addrs, "192.168.182.0/24").unwrap());
+        assert!(all_addrs_in_subnet(&[], "192.168.182.0/24").unwrap());
+    }
+
+    #[test]
+    fn test_not_all_addrs_in_subnet() {
+        let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1))];
+        assert!(!all_addrs_in_subnet(&addrs, "192.168.182.0/24").unwrap());
+        assert!(!all_addrs_in_subnet(&["::1".parse().unwrap()], "0.0.0.0/0").unwrap());
+        assert!(all_addrs_in_subnet(&addrs, "zoop").is_err());
+    }
+
+    #[test]
+    fn test_addr_in_sorted_subnets() {
+        let mut subnets: Vec<Subnet> = ["2001:db8::/32", "192.168.182.0/24", "10.0.0.0/8", "192.168.0.0/16", "10.1.0.0/16"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        subnets.sort();
+        assert!(addr_in_sorted_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1)), &subnets));
+        assert!(addr_in_sorted_subnets(&IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3)), &subnets));
+        assert!(addr_in_sorted_subnets(&"2001:db8::1".parse().unwrap(), &subnets));
+    }
+
+    #[test]
+    fn test_addr_not_in_sorted_subnets() {
+        let mut subnets: Vec<Subnet> = ["192.168.182.0/24", "0.0.0.0/1"].iter().map(|s| s.parse().unwrap()).collect();
+        subnets.sort();
+        assert!(!addr_in_sorted_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1)), &subnets));
+        assert!(!addr_in_sorted_subnets(&"::1".parse().unwrap(), &subnets));
+        assert!(!addr_in_sorted_subnets(&"::1".parse().unwrap(), &[]));
+    }
+
+    #[test]
+    fn test_share_a_subnet() {
+        let subnets = vec!["192.168.182.0/25", "192.168.0.0/16", "192.168.182.0/24"];
+        let (a, b) = (IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 182, 254)));
+        assert_eq!(share_a_subnet(&a, &b, &subnets).unwrap(), Some(1));
+        assert_eq!(share_a_subnet(&a, &a, &subnets).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_not_share_a_subnet() {
+        let subnets = vec!["192.168.182.0/24", "0.0.0.0/0"];
+        let a = IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1));
+        assert_eq!(share_a_subnet(&a, &"::1".parse().unwrap(), &subnets).unwrap(), None);
+        assert_eq!(share_a_subnet(&a, &a, &[]).unwrap(), None);
+        assert!(share_a_subnet(&a, &a, &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_sockaddr_in_subnet() {
+        assert!(sockaddr_in_subnet(&"192.168.182.1:80".parse().unwrap(), "192.168.182.0/24").unwrap());
+        assert!(!sockaddr_in_subnet(&"192.168.183.1:80".parse().unwrap(), "192.168.182.0/24").unwrap());
+        assert!(sockaddr_in_subnet(&"[2001:db8::1]:80".parse().unwrap(), "2001:db8::/32").unwrap());
+    }
+
+    #[test]
+    fn test_sockaddr_in_any_subnet() {
+        let subnets = vec!["192.168.181.0/24", "192.168.182.0/24"];
+        assert!(sockaddr_in_any_subnet(&"192.168.182.1:80".parse().unwrap(), &subnets).unwrap());
+        assert!(!sockaddr_in_any_subnet(&"[::1]:80".parse().unwrap(), &subnets).unwrap());
+        assert!(sockaddr_in_any_subnet(&"192.168.182.1:80".parse().unwrap(), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_addr_in_any_subnet_mixed_families() {
+        let subnets = vec!["2001:db8::/32", "2001:db8::/255.255.0.0", "192.168.182.0/24", "::/0"];
+        assert!(addr_in_any_subnet(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap());
+        assert!(!addr_in_any_subnet(&IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), &subnets).unwrap());
+        assert!(addr_in_any_subnet(&"::ffff:192.168.182.1".parse().unwrap(), &["::ffff:192.168.182.0/120"]).unwrap());
+        assert!(addr_in_any_subnet(&"2001:db8::1".parse().unwrap(), &subnets[2..]).unwrap());
+    }
+
+    #[test]
+    fn test_addr_in_any_subnet_malformed_in_either_family() {
+        for addr in ["10.0.0.1", "2001:db8::1"] {
+            let addr: IpAddr = addr.parse().unwrap();
+            for subnet in ["zoop", "zoop::", "zz::/999", "zz::/64", "10.0.0.0/255.0.255.0"] {
+                assert!(addr_in_any_subnet(&addr, &[subnet]).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_addr_in_any_subnet_like() {
+        let subnets: Vec<Subnet> = vec!["192.168.181.0/24".parse().unwrap(), "192.168.182.0/24".parse().unwrap()];
+        assert!(addr_in_any_subnet_like(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap());
+        assert!(!addr_in_all_subnets_like(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap());
+        let subnets = vec!["192.168.182.0/24".to_string(), "192.168.182.0/25".to_string()];
+        assert!(addr_in_all_subnets_like(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap());
+    }
+
+    #[test]
+    fn test_addr_in_subnets_empty() {
+        assert_eq!(addr_in_any_subnet(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &[]), Ok(false));
+        assert_eq!(addr_in_all_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &[]), Ok(true));
+    }
+
+    #[test]
+    fn test_addr_outside_all_subnets() {
+        let subnets = vec!["192.168.181.0/24", "192.168.182.0/24"];
+        assert!(addr_outside_all_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 183, 1)), &subnets).unwrap());
+        assert!(!addr_outside_all_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &subnets).unwrap());
+        assert!(addr_outside_all_subnets(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_any_addr_outside_all_subnets() {
+        let subnets = vec!["192.168.181.0/24", "192.168.182.0/24"];
+        let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 181, 1)), IpAddr::V4(Ipv4Addr::new(192, 168, 182, 2))];
+        assert!(!any_addr_outside_all_subnets(&addrs, &subnets).unwrap());
+        let addrs = vec![IpAddr::V4(Ipv4Addr::new(192, 168, 181, 1)), "::1".parse().unwrap()];
+        assert!(any_addr_outside_all_subnets(&addrs, &subnets).unwrap());
+        assert!(!any_addr_outside_all_subnets(&[], &subnets).unwrap());
+    }
+
+    #[test]
+    fn test_is_usable_in_any() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        let pools = vec!["192.168.182.0/24", "10.0.0.0/31", "10.0.1.1/32", "2001:db8::/64"];
+        assert!(is_usable_in_any(&addr("192.168.182.1"), &pools).unwrap());
+        assert!(is_usable_in_any(&addr("10.0.0.0"), &pools).unwrap());
+        assert!(is_usable_in_any(&addr("10.0.1.1"), &pools).unwrap());
+        assert!(is_usable_in_any(&addr("2001:db8::ffff:ffff:ffff:ffff"), &pools).unwrap());
+    }
+
+    #[test]
+    fn test_not_is_usable_in_any() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        let pools = vec!["192.168.182.0/24", "2001:db8::/64"];
+        assert!(!is_usable_in_any(&addr("192.168.182.0"), &pools).unwrap());
+        assert!(!is_usable_in_any(&addr("192.168.182.255"), &pools).unwrap());
+        assert!(!is_usable_in_any(&addr("2001:db8::"), &pools).unwrap());
+        assert!(!is_usable_in_any(&addr("10.0.0.1"), &pools).unwrap());
+        assert!(is_usable_in_any(&addr("10.0.0.1"), &["zoop"]).is_err());
+    }
+
+    #[test]
+    fn test_addr_in_any_subnet_lenient() {
+        let addr: IpAddr = "2001:db8::1".parse().unwrap();
+        assert!(addr_in_any_subnet_lenient(&addr, &["2001:db8::/129", "", "2001:db8::/32"]));
+        assert!(addr_in_any_subnet_lenient(&"10.0.0.1".parse().unwrap(), &["10.0.0.0/8"]));
+    }
+
+    #[test]
+    fn test_not_addr_in_any_subnet_lenient() {
+        let addr: IpAddr = "10.0.0.1".parse().unwrap();
+        assert!(!addr_in_any_subnet_lenient(&addr, &["zoop", "10.0.0.0/33", "10.0.0.0/zoop"]));
+        assert!(!addr_in_any_subnet_lenient(&addr, &["::/0", "11.0.0.0/8"]));
+        assert!(!addr_in_any_subnet_lenient(&addr, &[]));
+    }
+
+    #[test]
+    fn test_addr_in_subnet_set() {
+        let addr: IpAddr = "2001:db8::1".parse().unwrap();
+        let subnets: HashSet<&str> = ["10.0.0.0/8", "2001:db8::/32"].into();
+        assert!(addr_in_subnet_set(&addr, &subnets).unwrap());
+        let subnets: HashSet<Subnet> = ["10.0.0.0/8".parse().unwrap()].into();
+        assert!(addr_in_subnet_set(&"10.1.2.3".parse().unwrap(), &subnets).unwrap());
+    }
+
+    #[test]
+    fn test_not_addr_in_subnet_set() {
+        let addr: IpAddr = "10.0.0.1".parse().unwrap();
+        let subnets: HashSet<String> = ["11.0.0.0/8".to_string(), "2001:db8::/32".to_string()].into();
+        assert!(!addr_in_subnet_set(&addr, &subnets).unwrap());
+        assert!(!addr_in_subnet_set(&addr, &HashSet::<String>::new()).unwrap());
+        let subnets: HashSet<&str> = ["zoop"].into();
+        assert!(addr_in_subnet_set(&addr, &subnets).is_err());
+    }
+}
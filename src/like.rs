@@ -0,0 +1,170 @@
+use std::net::IpAddr;
+
+use netaddr2::{Contains, NetAddr};
+
+use crate::SubnetError;
+use crate::parse::parse_netaddr;
+use crate::subnet::{Family, Subnet, max_prefix};
+
+
+/// Something that can be checked for containing an address, so functions like
+/// [`addr_in_any_subnet_like`](crate::addr_in_any_subnet_like) accept slices of
+/// strings, parsed subnets or custom types alike.
+///
+/// Addresses of the other family never match.
+///
+/// # Examples
+///
+/// ### Check addresses against a custom subnet type.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::{Subnet, SubnetLike, addr_in_any_subnet_like};
+///
+/// struct Vlan {
+///     id: u16,
+///     subnet: Subnet,
+/// }
+///
+/// impl SubnetLike for Vlan {
+///     fn contains_addr(&self, addr: &IpAddr) -> bool {
+///         self.subnet.contains(addr)
+///     }
+/// }
+///
+/// let vlans = vec![Vlan { id: 182, subnet: "192.168.182.0/24".parse().unwrap() }];
+/// let res = addr_in_any_subnet_like(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1)), &vlans).unwrap();
+/// assert!(res);
+/// ```
+pub trait SubnetLike {
+    /// Check if the subnet contains `addr`. Subnets that can't be parsed never match.
+    fn contains_addr(&self, addr: &IpAddr) -> bool;
+
+    /// Like [`SubnetLike::contains_addr`], but returns an error if the subnet
+    /// can't be parsed. Types that are always valid needn't override this.
+    fn try_contains_addr(&self, addr: &IpAddr) -> Result<bool, SubnetError> {
+        Ok(self.contains_addr(addr))
+    }
+
+    /// The subnet's family if it's cheaper to tell than parsing the subnet, so
+    /// [`addr_in_any_subnet_like`](crate::addr_in_any_subnet_like) can skip subnets of
+    /// the other family. It must only be `Some` for subnets that are valid, so
+    /// skipping them never hides an error. The default of `None` means every
+    /// subnet is checked.
+    fn family_hint(&self) -> Option<Family> {
+        None
+    }
+}
+
+impl<T: SubnetLike + ?Sized> SubnetLike for &T {
+    fn contains_addr(&self, addr: &IpAddr) -> bool {
+        (**self).contains_addr(addr)
+    }
+
+    fn try_contains_addr(&self, addr: &IpAddr) -> Result<bool, SubnetError> {
+        (**self).try_contains_addr(addr)
+    }
+
+    fn family_hint(&self) -> Option<Family> {
+        (**self).family_hint()
+    }
+}
+
+impl SubnetLike for str {
+    fn contains_addr(&self, addr: &IpAddr) -> bool {
+        self.try_contains_addr(addr).unwrap_or(false)
+    }
+
+    fn try_contains_addr(&self, addr: &IpAddr) -> Result<bool, SubnetError> {
+        Ok(parse_netaddr(self)?.contains_addr(addr))
+    }
+
+    fn family_hint(&self) -> Option<Family> {
+        // Only plain `address/prefix` strings are classified, anything else is
+        // left to the full parse so its errors are still reported.
+        let (addr, prefix) = self.split_once('/')?;
+        if prefix.is_empty() || prefix.len() > 3 || !prefix.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let family = match addr.parse::<IpAddr>().ok()? {
+            IpAddr::V4(_) => Family::V4,
+            IpAddr::V6(_) => Family::V6,
+        };
+        (prefix.parse::<u8>().ok()? <= max_prefix(family == Family::V6)).then_some(family)
+    }
+}
+
+impl SubnetLike for String {
+    fn contains_addr(&self, addr: &IpAddr) -> bool {
+        self.as_str().contains_addr(addr)
+    }
+
+    fn try_contains_addr(&self, addr: &IpAddr) -> Result<bool, SubnetError> {
+        self.as_str().try_contains_addr(addr)
+    }
+
+    fn family_hint(&self) -> Option<Family> {
+        self.as_str().family_hint()
+    }
+}
+
+impl SubnetLike for Subnet {
+    fn contains_addr(&self, addr: &IpAddr) -> bool {
+        self.contains(addr)
+    }
+
+    fn family_hint(&self) -> Option<Family> {
+        Some(self.family())
+    }
+}
+
+impl SubnetLike for NetAddr {
+    fn contains_addr(&self, addr: &IpAddr) -> bool {
+        match self {
+            NetAddr::V4(subnet4) => addr.is_ipv4() && subnet4.contains(addr),
+            NetAddr::V6(subnet6) => addr.is_ipv6() && subnet6.contains(addr),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_like() {
+        let addr: IpAddr = "192.168.182.1".parse().unwrap();
+        assert!("192.168.182.0/24".contains_addr(&addr));
+        assert!("192.168.182.0/24".to_string().contains_addr(&addr));
+        assert!("192.168.182.0/24".parse::<Subnet>().unwrap().contains_addr(&addr));
+        assert!("192.168.182.0/24".parse::<NetAddr>().unwrap().contains_addr(&addr));
+        assert!(!"2001:db8::/32".parse::<NetAddr>().unwrap().contains_addr(&addr));
+        assert!(!"::/0".contains_addr(&addr));
+    }
+
+    #[test]
+    fn test_subnet_like_invalid() {
+        let addr: IpAddr = "192.168.182.1".parse().unwrap();
+        assert!(!"zoop".contains_addr(&addr));
+        assert!("zoop".try_contains_addr(&addr).is_err());
+        assert!(!"zoop".to_string().contains_addr(&addr));
+        assert!("zoop::".try_contains_addr(&addr).is_err());
+        assert!("zz::/999".to_string().try_contains_addr(&addr).is_err());
+    }
+
+    #[test]
+    fn test_subnet_like_family_hint() {
+        assert_eq!("2001:db8::/32".family_hint(), Some(Family::V6));
+        assert_eq!("10.0.0.0/8".to_string().family_hint(), Some(Family::V4));
+        for subnet in ["zoop", "zoop::/32", "zz::/999", "10.0.0.0/33", "2001:db8::/129", "10.0.0.0/+8", "10.0.0.0", "10.0.0.0/255.0.0.0"] {
+            assert_eq!(subnet.family_hint(), None);
+        }
+        for subnet in ["10.0.0.1/32", "0.0.0.0/0", "::/0", "2001:db8::1/128", "::ffff:10.0.0.0/104", "10.0.0.0/08"] {
+            assert!(parse_netaddr(subnet).is_ok());
+            assert!(subnet.family_hint().is_some());
+        }
+        assert_eq!("10.0.0.0/8".parse::<Subnet>().unwrap().family_hint(), Some(Family::V4));
+        assert_eq!("10.0.0.0/8".parse::<NetAddr>().unwrap().family_hint(), None);
+    }
+}
@@ -0,0 +1,283 @@
+use std::borrow::Cow;
+use std::net::IpAddr;
+
+use netaddr2::{NetAddr, Netv4Addr, Netv6Addr};
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, max_prefix};
+
+
+/// Trim surrounding whitespace and lowercase IPv6 hex digits.
+pub(crate) fn normalize(s: &str) -> Cow<'_, str> {
+    let s = s.trim();
+    if s.contains(':') && s.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(s.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Parse a `NetAddr` after normalizing the input, rejecting netmasks that
+/// aren't contiguous and so have no CIDR prefix.
+pub(crate) fn parse_netaddr(s: &str) -> Result<NetAddr, SubnetError> {
+    let net = normalize(s).parse::<NetAddr>()?;
+    if !net.is_cidr() {
+        return Err(SubnetError::NonContiguousMask { mask: net.mask().to_string() });
+    }
+    Ok(net)
+}
+
+
+/// Returns the canonical CIDR form of `subnet` and whether the input had host
+/// bits set, i.e. whether canonicalizing it changed the address.
+///
+/// # Examples
+///
+/// ### Align a subnet to its network address.
+///
+/// ```
+/// use subnet_utils::align;
+///
+/// let (res, changed) = align("192.168.182.1/24").unwrap();
+/// assert_eq!(res, "192.168.182.0/24");
+/// assert!(changed);
+/// ```
+pub fn align(subnet: &str) -> Result<(String, bool), SubnetError> {
+    let parsed: Subnet = subnet.parse()?;
+    let changed = normalize(subnet)
+        .split(['/', ' '])
+        .next()
+        .and_then(|addr| addr.parse::<IpAddr>().ok())
+        .is_some_and(|addr| addr != parsed.network());
+    Ok((parsed.to_string(), changed))
+}
+
+/// The canonical CIDR form of each of `subnets`, with `None` for entries that
+/// fail to parse, alongside the index and error of each failure. Unlike
+/// [`align`], a bad entry doesn't stop the rest from being canonicalized.
+///
+/// # Examples
+///
+/// ### Canonicalize a list with a bad entry.
+///
+/// ```
+/// use subnet_utils::canonicalize_all;
+///
+/// let (res, errors) = canonicalize_all(&["192.168.182.1/24", "zoop", "2001:DB8::/32"]);
+/// assert_eq!(res, vec![Some("192.168.182.0/24".to_string()), None, Some("2001:db8::/32".to_string())]);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].0, 1);
+/// ```
+pub fn canonicalize_all(subnets: &[&str]) -> (Vec<Option<String>>, Vec<(usize, SubnetError)>) {
+    let mut errors = Vec::new();
+    let res = subnets
+        .iter()
+        .enumerate()
+        .map(|(index, subnet)| match subnet.parse::<Subnet>() {
+            Ok(subnet) => Some(subnet.to_string()),
+            Err(err) => {
+                errors.push((index, err));
+                None
+            }
+        })
+        .collect();
+    (res, errors)
+}
+
+
+/// Parse a subnet, also accepting octet-truncated IPv4 forms such as `10/8`
+/// or `192.168/16`, whose missing trailing octets are taken as zero. IPv6
+/// input is parsed as normal.
+///
+/// # Examples
+///
+/// ### Parse a truncated IPv4 subnet.
+///
+/// ```
+/// use subnet_utils::parse_lenient;
+///
+/// let res = parse_lenient("192.168/16").unwrap();
+/// assert_eq!(res.to_string(), "192.168.0.0/16");
+/// ```
+pub fn parse_lenient(s: &str) -> Result<Subnet, SubnetError> {
+    let s = s.trim();
+    if let Some((addr, prefix)) = s.split_once('/')
+        && !addr.contains(':')
+    {
+        let octets = addr.split('.').count();
+        if octets < 4 && addr.split('.').all(|o| !o.is_empty() && o.bytes().all(|b| b.is_ascii_digit())) {
+            return format!("{}{}/{}", addr, ".0".repeat(4 - octets), prefix).parse();
+        }
+    }
+    s.parse()
+}
+
+
+/// Parse an IPv4 subnet as the underlying `netaddr2` type, returning
+/// [`SubnetError::FamilyMismatch`] for IPv6 input.
+///
+/// # Examples
+///
+/// ### Parse an IPv4 subnet as a `Netv4Addr`.
+///
+/// ```
+/// use std::net::Ipv4Addr;
+/// use subnet_utils::parse_v4;
+///
+/// let res = parse_v4("192.168.182.1/24").unwrap();
+/// assert_eq!(res.addr(), Ipv4Addr::new(192, 168, 182, 0));
+/// ```
+pub fn parse_v4(s: &str) -> Result<Netv4Addr, SubnetError> {
+    match parse_netaddr(s)? {
+        NetAddr::V4(net) => Ok(net),
+        NetAddr::V6(_) => Err(SubnetError::FamilyMismatch),
+    }
+}
+
+/// Parse an IPv6 subnet as the underlying `netaddr2` type, returning
+/// [`SubnetError::FamilyMismatch`] for IPv4 input.
+///
+/// # Examples
+///
+/// ### Parse an IPv6 subnet as a `Netv6Addr`.
+///
+/// ```
+/// use std::net::Ipv6Addr;
+/// use subnet_utils::parse_v6;
+///
+/// let res = parse_v6("2001:db8::1/32").unwrap();
+/// assert_eq!(res.addr(), "2001:db8::".parse::<Ipv6Addr>().unwrap());
+/// ```
+pub fn parse_v6(s: &str) -> Result<Netv6Addr, SubnetError> {
+    match parse_netaddr(s)? {
+        NetAddr::V6(net) => Ok(net),
+        NetAddr::V4(_) => Err(SubnetError::FamilyMismatch),
+    }
+}
+
+
+/// Parse a subnet, also accepting a bare address as its host route, a `/32`
+/// or `/128`.
+///
+/// # Examples
+///
+/// ### Parse an address or a subnet.
+///
+/// ```
+/// use subnet_utils::parse_addr_or_subnet;
+///
+/// assert_eq!(parse_addr_or_subnet("10.0.0.5").unwrap().to_string(), "10.0.0.5/32");
+/// assert_eq!(parse_addr_or_subnet("10.0.0.0/24").unwrap().to_string(), "10.0.0.0/24");
+/// ```
+pub fn parse_addr_or_subnet(s: &str) -> Result<Subnet, SubnetError> {
+    match normalize(s).parse::<IpAddr>() {
+        Ok(addr) => Subnet::from_parts(addr, max_prefix(addr.is_ipv6())),
+        Err(_) => s.parse(),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align() {
+        let res = align("192.168.182.1/24").unwrap();
+        assert_eq!(res, ("192.168.182.0/24".to_string(), true));
+        let res = align("2001:db8::1/32").unwrap();
+        assert_eq!(res, ("2001:db8::/32".to_string(), true));
+    }
+
+    #[test]
+    fn test_align_unchanged() {
+        let res = align("192.168.182.0/24").unwrap();
+        assert_eq!(res, ("192.168.182.0/24".to_string(), false));
+        let res = align("192.168.182.0/255.255.255.0").unwrap();
+        assert_eq!(res, ("192.168.182.0/24".to_string(), false));
+    }
+
+    #[test]
+    fn test_parse_netaddr_messy_input() {
+        let res = parse_netaddr(" 2001:DB8::/32 ").unwrap();
+        assert_eq!(res, "2001:db8::/32".parse::<NetAddr>().unwrap());
+        let res = parse_netaddr("\t192.168.182.0/24\n").unwrap();
+        assert_eq!(res, "192.168.182.0/24".parse::<NetAddr>().unwrap());
+        let res = parse_netaddr("  192.168.182.0 255.255.255.0  ").unwrap();
+        assert_eq!(res, "192.168.182.0/24".parse::<NetAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_align_messy_input() {
+        let res = align(" 2001:DB8::1/32 ").unwrap();
+        assert_eq!(res, ("2001:db8::/32".to_string(), true));
+        let res = align("2001:DB8::/32").unwrap();
+        assert_eq!(res, ("2001:db8::/32".to_string(), false));
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        assert_eq!(parse_lenient("10/8").unwrap().to_string(), "10.0.0.0/8");
+        assert_eq!(parse_lenient("192.168/16").unwrap().to_string(), "192.168.0.0/16");
+        assert_eq!(parse_lenient("172.16.5/24").unwrap().to_string(), "172.16.5.0/24");
+        assert_eq!(parse_lenient("192.168.182.0/24").unwrap().to_string(), "192.168.182.0/24");
+        assert_eq!(parse_lenient(" 2001:DB8::/32 ").unwrap().to_string(), "2001:db8::/32");
+    }
+
+    #[test]
+    fn test_parse_lenient_invalid() {
+        assert!(parse_lenient("10../8").is_err());
+        assert!(parse_lenient("256/8").is_err());
+        assert!(parse_lenient("10/33").is_err());
+        assert!(parse_lenient("zoop/8").is_err());
+        assert!(parse_lenient("10").is_err());
+    }
+
+    #[test]
+    fn test_parse_v4() {
+        let res = parse_v4(" 192.168.182.0/24 ").unwrap();
+        assert_eq!(res, "192.168.182.0/24".parse::<Netv4Addr>().unwrap());
+        assert_eq!(parse_v4("2001:db8::/32"), Err(SubnetError::FamilyMismatch));
+        assert!(parse_v4("zoop").is_err());
+    }
+
+    #[test]
+    fn test_parse_v6() {
+        let res = parse_v6("2001:DB8::/32").unwrap();
+        assert_eq!(res, "2001:db8::/32".parse::<Netv6Addr>().unwrap());
+        assert_eq!(parse_v6("192.168.182.0/24"), Err(SubnetError::FamilyMismatch));
+    }
+
+    #[test]
+    fn test_parse_addr_or_subnet() {
+        assert_eq!(parse_addr_or_subnet(" 2001:DB8::1 ").unwrap().to_string(), "2001:db8::1/128");
+        assert_eq!(parse_addr_or_subnet("2001:db8::1/32").unwrap().to_string(), "2001:db8::/32");
+        assert_eq!(parse_addr_or_subnet("10.0.0.0 255.255.0.0").unwrap().to_string(), "10.0.0.0/16");
+        assert_eq!(parse_addr_or_subnet("0.0.0.0").unwrap().to_string(), "0.0.0.0/32");
+    }
+
+    #[test]
+    fn test_parse_addr_or_subnet_invalid() {
+        assert!(parse_addr_or_subnet("zoop").is_err());
+        assert!(parse_addr_or_subnet("10.0.0.256").is_err());
+        assert!(parse_addr_or_subnet("10.0.0.0/33").is_err());
+        assert!(parse_addr_or_subnet("").is_err());
+    }
+
+    #[test]
+    fn test_canonicalize_all() {
+        let (res, errors) = canonicalize_all(&[" 10.0.0.1/8 ", "10.0.0.0 255.255.0.0", "2001:db8::1/128"]);
+        assert_eq!(res, vec![Some("10.0.0.0/8".to_string()), Some("10.0.0.0/16".to_string()), Some("2001:db8::1/128".to_string())]);
+        assert!(errors.is_empty());
+        let (res, errors) = canonicalize_all(&[]);
+        assert!(res.is_empty() && errors.is_empty());
+    }
+
+    #[test]
+    fn test_canonicalize_all_errors() {
+        let (res, errors) = canonicalize_all(&["zoop", "10.0.0.0/8", "10.0.0.0/33", ""]);
+        assert_eq!(res, vec![None, Some("10.0.0.0/8".to_string()), None, None]);
+        assert_eq!(errors.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 2, 3]);
+        assert_eq!(errors[1].1, "10.0.0.0/33".parse::<Subnet>().unwrap_err());
+    }
+}
@@ -0,0 +1,227 @@
+use crate::SubnetError;
+use crate::aggregate::aggregate;
+use crate::subnet::{Subnet, check_prefix, host_mask};
+
+
+/// The fraction (`0.0` to `1.0`) of `parent` covered by the `allocated` subnets.
+///
+/// Overlapping allocations are only counted once, and allocations outside
+/// `parent` are ignored.
+///
+/// # Examples
+///
+/// ### Calculate how much of a subnet is allocated.
+///
+/// ```
+/// use subnet_utils::utilization;
+///
+/// let allocated = vec!["10.0.0.0/25", "10.0.0.0/26", "10.0.1.0/24"];
+/// let res = utilization("10.0.0.0/24", &allocated).unwrap();
+/// assert_eq!(res, 0.5);
+/// ```
+pub fn utilization(parent: &str, allocated: &[&str]) -> Result<f64, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    let mut inside = Vec::new();
+    for subnet in allocated.iter() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.contains_subnet(&parent) {
+            return Ok(1.0);
+        }
+        if parent.contains_subnet(&subnet) {
+            inside.push(subnet);
+        }
+    }
+    let used: f64 = aggregate(inside).iter().map(|s| s.size() as f64).sum();
+    Ok(used / parent.size() as f64)
+}
+
+/// The lowest-addressed block of length `prefix` within `parent` that doesn't
+/// overlap any of the `allocated` subnets, or `None` if no such block fits.
+/// Allocations outside `parent` are ignored.
+///
+/// # Examples
+///
+/// ### Find the first free /26 in a subnet.
+///
+/// ```
+/// use subnet_utils::first_free_subnet;
+///
+/// let allocated = vec!["10.0.0.0/26", "10.0.0.96/27"];
+/// let res = first_free_subnet("10.0.0.0/24", &allocated, 26).unwrap();
+/// assert_eq!(res.as_deref(), Some("10.0.0.128/26"));
+/// ```
+pub fn first_free_subnet(parent: &str, allocated: &[&str], prefix: u8) -> Result<Option<String>, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    let v6 = parent.is_ipv6();
+    check_prefix(prefix, v6)?;
+    if prefix < parent.prefix_len() {
+        return Ok(None);
+    }
+    let start = first_fit(&free_ranges(&parent, allocated)?, prefix, v6);
+    Ok(start.map(|start| Subnet::from_bits(start, prefix, v6).to_string()))
+}
+
+/// The shortest prefix length for which a free block still fits in `parent`
+/// besides the `allocated` subnets, i.e. the largest block that can still be
+/// handed out, or `None` if `parent` is fully allocated. Allocations outside
+/// `parent` are ignored.
+///
+/// # Examples
+///
+/// ### Find the largest free block in a subnet.
+///
+/// ```
+/// use subnet_utils::largest_free_prefix;
+///
+/// let allocated = vec!["10.0.0.0/25", "10.0.0.192/27"];
+/// let res = largest_free_prefix("10.0.0.0/24", &allocated).unwrap();
+/// assert_eq!(res, Some(26));
+/// ```
+pub fn largest_free_prefix(parent: &str, allocated: &[&str]) -> Result<Option<u8>, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    let ranges = free_ranges(&parent, allocated)?;
+    let v6 = parent.is_ipv6();
+    Ok((parent.prefix_len()..=parent.max_prefix()).find(|prefix| first_fit(&ranges, *prefix, v6).is_some()))
+}
+
+/// The fraction (`0.0` to `1.0`) of the usable host addresses of `subnet` in
+/// use, given only how many are `used`, see [`describe`](crate::describe) for
+/// which addresses are usable.
+///
+/// Counts above the usable host count are clamped to `1.0`, but a count that
+/// exceeds it by more than the subnet's size can't be right and returns
+/// [`SubnetError::TooLarge`].
+///
+/// # Examples
+///
+/// ### Calculate utilization from a count of used addresses.
+///
+/// ```
+/// use subnet_utils::utilization_from_count;
+///
+/// let res = utilization_from_count("10.0.0.0/30", 1).unwrap();
+/// assert_eq!(res, 0.5);
+/// ```
+pub fn utilization_from_count(subnet: &str, used: u128) -> Result<f64, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let (first, last) = subnet.usable();
+    let usable = last - first + 1;
+    let limit = usable.saturating_add(subnet.size());
+    if used > limit {
+        return Err(SubnetError::TooLarge { size: used, limit });
+    }
+    Ok((used as f64 / usable as f64).min(1.0))
+}
+
+/// The inclusive integer ranges of `parent` not covered by `allocated`, in order.
+pub(crate) fn free_ranges(parent: &Subnet, allocated: &[&str]) -> Result<Vec<(u128, u128)>, SubnetError> {
+    let mut inside = Vec::new();
+    for subnet in allocated.iter() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.contains_subnet(parent) {
+            return Ok(Vec::new());
+        }
+        if parent.contains_subnet(&subnet) {
+            inside.push(subnet);
+        }
+    }
+    let mut ranges = Vec::new();
+    let mut cursor = Some(parent.first());
+    for subnet in aggregate(inside) {
+        if let Some(next) = cursor
+            && next < subnet.first()
+        {
+            ranges.push((next, subnet.first() - 1));
+        }
+        cursor = subnet.last().checked_add(1);
+    }
+    if let Some(next) = cursor
+        && next <= parent.last()
+    {
+        ranges.push((next, parent.last()));
+    }
+    Ok(ranges)
+}
+
+/// The lowest network address of a block of length `prefix` that fits in one
+/// of the inclusive `ranges`.
+fn first_fit(ranges: &[(u128, u128)], prefix: u8, v6: bool) -> Option<u128> {
+    let host = host_mask(prefix, v6);
+    ranges.iter().find_map(|&(lo, hi)| {
+        let start = if lo & host == 0 { Some(lo) } else { (lo | host).checked_add(1) };
+        start.filter(|start| start | host <= hi)
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utilization() {
+        let allocated = vec!["10.0.0.0/25", "10.0.0.0/26", "10.0.0.192/26", "10.0.1.0/24"];
+        let res = utilization("10.0.0.0/24", &allocated).unwrap();
+        assert_eq!(res, 0.75);
+    }
+
+    #[test]
+    fn test_utilization_full() {
+        let res = utilization("10.0.0.0/24", &["10.0.0.0/16"]).unwrap();
+        assert_eq!(res, 1.0);
+        let res = utilization("2001:db8::/32", &["10.0.0.0/8"]).unwrap();
+        assert_eq!(res, 0.0);
+    }
+
+    #[test]
+    fn test_first_free_subnet() {
+        let allocated = vec!["10.0.0.0/26", "10.0.0.96/27", "10.1.0.0/24"];
+        let res = first_free_subnet("10.0.0.0/24", &allocated, 27).unwrap();
+        assert_eq!(res.as_deref(), Some("10.0.0.64/27"));
+        let res = first_free_subnet("10.0.0.0/24", &allocated, 25).unwrap();
+        assert_eq!(res.as_deref(), Some("10.0.0.128/25"));
+        let res = first_free_subnet("::/0", &[], 0).unwrap();
+        assert_eq!(res.as_deref(), Some("::/0"));
+    }
+
+    #[test]
+    fn test_first_free_subnet_none() {
+        let allocated = vec!["10.0.0.0/25", "10.0.0.192/26"];
+        assert_eq!(first_free_subnet("10.0.0.0/24", &allocated, 25).unwrap(), None);
+        assert_eq!(first_free_subnet("10.0.0.0/24", &allocated, 23).unwrap(), None);
+        assert_eq!(first_free_subnet("10.0.0.0/24", &["10.0.0.0/8"], 32).unwrap(), None);
+        assert!(first_free_subnet("10.0.0.0/24", &allocated, 33).is_err());
+    }
+
+    #[test]
+    fn test_utilization_from_count() {
+        assert_eq!(utilization_from_count("10.0.0.0/24", 127).unwrap(), 0.5);
+        assert_eq!(utilization_from_count("10.0.0.0/24", 0).unwrap(), 0.0);
+        assert_eq!(utilization_from_count("10.0.0.0/31", 1).unwrap(), 0.5);
+        assert_eq!(utilization_from_count("10.0.0.0/24", 300).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_utilization_from_count_too_large() {
+        let res = utilization_from_count("10.0.0.0/24", 511);
+        assert_eq!(res, Err(SubnetError::TooLarge { size: 511, limit: 510 }));
+        assert!(utilization_from_count("::/0", u128::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_largest_free_prefix() {
+        let allocated = vec!["10.0.0.0/26", "10.0.0.128/26", "10.1.0.0/16"];
+        assert_eq!(largest_free_prefix("10.0.0.0/24", &allocated).unwrap(), Some(26));
+        assert_eq!(largest_free_prefix("10.0.0.0/24", &[]).unwrap(), Some(24));
+        assert_eq!(largest_free_prefix("10.0.0.0/24", &["10.0.0.1/32"]).unwrap(), Some(25));
+        assert_eq!(largest_free_prefix("::/0", &["::/1"]).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_largest_free_prefix_none() {
+        let allocated = vec!["10.0.0.0/25", "10.0.0.128/25"];
+        assert_eq!(largest_free_prefix("10.0.0.0/24", &allocated).unwrap(), None);
+        assert_eq!(largest_free_prefix("10.0.0.0/24", &["0.0.0.0/0"]).unwrap(), None);
+        assert!(largest_free_prefix("10.0.0.0/24", &["zoop"]).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, addr_to_bits, bits_to_addr, check_prefix, host_mask, max_prefix};
+
+
+/// The maximum number of addresses [`boundaries`] will return.
+pub const BOUNDARIES_LIMIT: u128 = 65_536;
+
+/// Every network address of length `prefix` within the inclusive range
+/// `start..=end`, in order. Returns [`SubnetError::TooLarge`] rather than
+/// more than [`BOUNDARIES_LIMIT`] addresses, so wide ranges need a coarse
+/// enough `prefix`.
+///
+/// # Examples
+///
+/// ### Find the /24 boundaries crossed by a range.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::boundaries;
+///
+/// let start = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 128));
+/// let end = IpAddr::V4(Ipv4Addr::new(10, 0, 2, 0));
+/// let res = boundaries(&start, &end, 24).unwrap();
+/// assert_eq!(res, vec![IpAddr::V4(Ipv4Addr::new(10, 0, 1, 0)), IpAddr::V4(Ipv4Addr::new(10, 0, 2, 0))]);
+/// ```
+pub fn boundaries(start: &IpAddr, end: &IpAddr, prefix: u8) -> Result<Vec<IpAddr>, SubnetError> {
+    let (lo, hi, v6) = range_bits(start, end)?;
+    check_prefix(prefix, v6)?;
+    let host = host_mask(prefix, v6);
+    let mut next = if lo & host == 0 { Some(lo) } else { (lo | host).checked_add(1) };
+    let shift = (max_prefix(v6) - prefix) as u32;
+    let count = next
+        .filter(|first| *first <= hi)
+        .map_or(0, |first| (hi - first).checked_shr(shift).unwrap_or(0).saturating_add(1));
+    if count > BOUNDARIES_LIMIT {
+        return Err(SubnetError::TooLarge { size: count, limit: BOUNDARIES_LIMIT });
+    }
+    let mut res = Vec::with_capacity(count as usize);
+    while let Some(current) = next.filter(|n| *n <= hi) {
+        res.push(bits_to_addr(current, v6));
+        next = current.checked_add(host).and_then(|n| n.checked_add(1));
+    }
+    Ok(res)
+}
+
+/// Check if any address in the inclusive range `start..=end` is in any of the
+/// subnets. Subnets of the other family never overlap.
+///
+/// # Examples
+///
+/// ### Check if a range overlaps any subnet.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::range_overlaps_any;
+///
+/// let start = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 200));
+/// let end = IpAddr::V4(Ipv4Addr::new(10, 0, 1, 10));
+/// let res = range_overlaps_any(&start, &end, &["10.0.1.0/24"]).unwrap();
+/// assert!(res);
+/// ```
+pub fn range_overlaps_any(start: &IpAddr, end: &IpAddr, subnets: &[&str]) -> Result<bool, SubnetError> {
+    let (lo, hi, v6) = range_bits(start, end)?;
+    for subnet in subnets.iter() {
+        let subnet: Subnet = subnet.parse()?;
+        if subnet.is_ipv6() == v6 && subnet.first() <= hi && lo <= subnet.last() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Widen the inclusive range `start..=end` to whole blocks of length
+/// `prefix`, rounding `start` down to the network address of its block and
+/// `end` up to the last address of its block.
+///
+/// # Examples
+///
+/// ### Snap a range to /24 boundaries.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::align_range;
+///
+/// let start = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 200));
+/// let end = IpAddr::V4(Ipv4Addr::new(10, 0, 2, 5));
+/// let res = align_range(&start, &end, 24).unwrap();
+/// assert_eq!(res, (IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), IpAddr::V4(Ipv4Addr::new(10, 0, 2, 255))));
+/// ```
+pub fn align_range(start: &IpAddr, end: &IpAddr, prefix: u8) -> Result<(IpAddr, IpAddr), SubnetError> {
+    let (lo, hi, v6) = range_bits(start, end)?;
+    check_prefix(prefix, v6)?;
+    let host = host_mask(prefix, v6);
+    Ok((bits_to_addr(lo & !host, v6), bits_to_addr(hi | host, v6)))
+}
+
+/// The number of blocks of length `prefix` needed to cover the inclusive
+/// range `start..=end`, i.e. the blocks spanned by the range that
+/// [`align_range`] widens it to. Saturates at `u128::MAX` when every IPv6
+/// `/128` is needed.
+///
+/// # Examples
+///
+/// ### Count the /24s a range touches.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::blocks_needed;
+///
+/// let start = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 200));
+/// let end = IpAddr::V4(Ipv4Addr::new(10, 0, 2, 5));
+/// assert_eq!(blocks_needed(&start, &end, 24).unwrap(), 3);
+/// ```
+pub fn blocks_needed(start: &IpAddr, end: &IpAddr, prefix: u8) -> Result<u128, SubnetError> {
+    let (lo, hi, v6) = range_bits(start, end)?;
+    check_prefix(prefix, v6)?;
+    let shift = (max_prefix(v6) - prefix) as u32;
+    let (lo, hi) = (lo.checked_shr(shift).unwrap_or(0), hi.checked_shr(shift).unwrap_or(0));
+    Ok((hi - lo).saturating_add(1))
+}
+
+/// The block of length `prefix` containing the midpoint of the inclusive
+/// range `start..=end`, rounding down when the range has an even number of
+/// addresses, for splitting a free range roughly in half.
+///
+/// # Examples
+///
+/// ### Find the /24 in the middle of a range.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::midpoint_subnet;
+///
+/// let start = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0));
+/// let end = IpAddr::V4(Ipv4Addr::new(10, 0, 255, 255));
+/// assert_eq!(midpoint_subnet(&start, &end, 24).unwrap(), "10.0.127.0/24");
+/// ```
+pub fn midpoint_subnet(start: &IpAddr, end: &IpAddr, prefix: u8) -> Result<String, SubnetError> {
+    let (lo, hi, v6) = range_bits(start, end)?;
+    check_prefix(prefix, v6)?;
+    Ok(Subnet::from_bits(lo + (hi - lo) / 2, prefix, v6).to_string())
+}
+
+/// The integer bounds and family of an inclusive address range.
+pub(crate) fn range_bits(start: &IpAddr, end: &IpAddr) -> Result<(u128, u128, bool), SubnetError> {
+    if start.is_ipv6() != end.is_ipv6() {
+        return Err(SubnetError::FamilyMismatch);
+    }
+    let (lo, hi) = (addr_to_bits(start), addr_to_bits(end));
+    if lo > hi {
+        return Err(SubnetError::InvalidRange);
+    }
+    Ok((lo, hi, start.is_ipv6()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundaries() {
+        let res = boundaries(&"10.0.0.0".parse().unwrap(), &"10.0.1.255".parse().unwrap(), 24).unwrap();
+        assert_eq!(res, vec!["10.0.0.0".parse::<IpAddr>().unwrap(), "10.0.1.0".parse().unwrap()]);
+        let res = boundaries(&"255.255.255.0".parse().unwrap(), &"255.255.255.255".parse().unwrap(), 25).unwrap();
+        assert_eq!(res, vec!["255.255.255.0".parse::<IpAddr>().unwrap(), "255.255.255.128".parse().unwrap()]);
+        let res = boundaries(&"10.0.0.1".parse().unwrap(), &"10.0.0.254".parse().unwrap(), 24).unwrap();
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn test_boundaries_invalid() {
+        let res = boundaries(&"10.0.0.0".parse().unwrap(), &"::1".parse().unwrap(), 24);
+        assert_eq!(res, Err(SubnetError::FamilyMismatch));
+        let res = boundaries(&"10.0.1.0".parse().unwrap(), &"10.0.0.0".parse().unwrap(), 24);
+        assert_eq!(res, Err(SubnetError::InvalidRange));
+        let res = boundaries(&"10.0.0.0".parse().unwrap(), &"10.0.1.0".parse().unwrap(), 33);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+    }
+
+    #[test]
+    fn test_boundaries_too_large() {
+        let (start, end) = ("::".parse().unwrap(), "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap());
+        let res = boundaries(&start, &end, 128);
+        assert_eq!(res, Err(SubnetError::TooLarge { size: u128::MAX, limit: BOUNDARIES_LIMIT }));
+        assert_eq!(boundaries(&start, &end, 16).unwrap().len(), 65_536);
+        let res = boundaries(&"10.0.0.0".parse().unwrap(), &"10.1.0.0".parse().unwrap(), 32);
+        assert_eq!(res, Err(SubnetError::TooLarge { size: 65_537, limit: BOUNDARIES_LIMIT }));
+        assert_eq!(boundaries(&"10.0.0.1".parse().unwrap(), &"10.1.0.0".parse().unwrap(), 32).unwrap().len(), 65_536);
+    }
+
+    #[test]
+    fn test_range_overlaps_any() {
+        let subnets = vec!["10.0.0.0/24", "2001:db8::/32"];
+        assert!(range_overlaps_any(&"9.0.0.0".parse().unwrap(), &"10.0.0.0".parse().unwrap(), &subnets).unwrap());
+        assert!(range_overlaps_any(&"10.0.0.255".parse().unwrap(), &"10.0.0.255".parse().unwrap(), &subnets).unwrap());
+        assert!(range_overlaps_any(&"::".parse().unwrap(), &"ffff::".parse().unwrap(), &subnets).unwrap());
+    }
+
+    #[test]
+    fn test_range_not_overlaps_any() {
+        let subnets = vec!["10.0.0.0/24", "::/0"];
+        assert!(!range_overlaps_any(&"10.0.1.0".parse().unwrap(), &"10.0.1.255".parse().unwrap(), &subnets).unwrap());
+        let res = range_overlaps_any(&"10.0.1.0".parse().unwrap(), &"10.0.0.0".parse().unwrap(), &subnets);
+        assert_eq!(res, Err(SubnetError::InvalidRange));
+        let res = range_overlaps_any(&"10.0.0.0".parse().unwrap(), &"::1".parse().unwrap(), &subnets);
+        assert_eq!(res, Err(SubnetError::FamilyMismatch));
+    }
+
+    #[test]
+    fn test_align_range() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        let res = align_range(&addr("10.0.0.0"), &addr("10.0.0.255"), 24).unwrap();
+        assert_eq!(res, (addr("10.0.0.0"), addr("10.0.0.255")));
+        let res = align_range(&addr("10.0.0.7"), &addr("10.0.0.7"), 30).unwrap();
+        assert_eq!(res, (addr("10.0.0.4"), addr("10.0.0.7")));
+        let res = align_range(&addr("10.1.2.3"), &addr("10.1.2.4"), 32).unwrap();
+        assert_eq!(res, (addr("10.1.2.3"), addr("10.1.2.4")));
+        let res = align_range(&addr("2001:db8::1"), &addr("2001:db8:0:1::1"), 64).unwrap();
+        assert_eq!(res, (addr("2001:db8::"), addr("2001:db8:0:1:ffff:ffff:ffff:ffff")));
+        let res = align_range(&addr("::1"), &addr("::2"), 0).unwrap();
+        assert_eq!(res, (addr("::"), addr("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")));
+    }
+
+    #[test]
+    fn test_align_range_invalid() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(align_range(&addr("10.0.0.0"), &addr("::1"), 24), Err(SubnetError::FamilyMismatch));
+        assert_eq!(align_range(&addr("10.0.0.2"), &addr("10.0.0.1"), 24), Err(SubnetError::InvalidRange));
+        assert!(matches!(align_range(&addr("10.0.0.0"), &addr("10.0.0.1"), 33), Err(SubnetError::InvalidPrefix { .. })));
+    }
+
+    #[test]
+    fn test_blocks_needed() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(blocks_needed(&addr("10.0.0.0"), &addr("10.0.0.255"), 24).unwrap(), 1);
+        assert_eq!(blocks_needed(&addr("10.0.0.255"), &addr("10.0.1.0"), 24).unwrap(), 2);
+        assert_eq!(blocks_needed(&addr("10.0.0.1"), &addr("10.0.0.1"), 32).unwrap(), 1);
+        assert_eq!(blocks_needed(&addr("0.0.0.0"), &addr("255.255.255.255"), 32).unwrap(), 1 << 32);
+        assert_eq!(blocks_needed(&addr("::"), &addr("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"), 0).unwrap(), 1);
+        assert_eq!(blocks_needed(&addr("::"), &addr("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"), 128).unwrap(), u128::MAX);
+    }
+
+    #[test]
+    fn test_blocks_needed_invalid() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(blocks_needed(&addr("10.0.0.0"), &addr("::1"), 24), Err(SubnetError::FamilyMismatch));
+        assert_eq!(blocks_needed(&addr("10.0.0.2"), &addr("10.0.0.1"), 24), Err(SubnetError::InvalidRange));
+        assert!(matches!(blocks_needed(&addr("::"), &addr("::1"), 129), Err(SubnetError::InvalidPrefix { .. })));
+    }
+
+    #[test]
+    fn test_midpoint_subnet() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(midpoint_subnet(&addr("10.0.0.0"), &addr("10.0.0.2"), 32).unwrap(), "10.0.0.1/32");
+        assert_eq!(midpoint_subnet(&addr("10.0.0.5"), &addr("10.0.0.5"), 30).unwrap(), "10.0.0.4/30");
+        assert_eq!(midpoint_subnet(&addr("0.0.0.0"), &addr("255.255.255.255"), 1).unwrap(), "0.0.0.0/1");
+        let res = midpoint_subnet(&addr("::"), &addr("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"), 128).unwrap();
+        assert_eq!(res, "7fff:ffff:ffff:ffff:ffff:ffff:ffff:ffff/128");
+    }
+
+    #[test]
+    fn test_midpoint_subnet_invalid() {
+        let addr = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(midpoint_subnet(&addr("10.0.0.0"), &addr("::1"), 24), Err(SubnetError::FamilyMismatch));
+        assert_eq!(midpoint_subnet(&addr("10.0.0.2"), &addr("10.0.0.1"), 24), Err(SubnetError::InvalidRange));
+        assert!(matches!(midpoint_subnet(&addr("10.0.0.0"), &addr("10.0.0.1"), 33), Err(SubnetError::InvalidPrefix { .. })));
+    }
+}
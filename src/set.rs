@@ -0,0 +1,354 @@
+use std::net::IpAddr;
+
+use crate::SubnetError;
+use crate::subnet::{Subnet, addr_to_bits, bits_to_addr, max_prefix};
+
+
+const MAGIC: &[u8; 4] = b"SNET";
+const VERSION: u8 = 1;
+
+/// A collection of parsed subnets for repeated containment checks.
+///
+/// Subnets are indexed in a binary trie, so lookups take time proportional to
+/// the address width rather than the number of subnets.
+///
+/// # Examples
+///
+/// ### Check if a set of subnets contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::SubnetSet;
+///
+/// let set = SubnetSet::from_strs(&["192.168.181.0/24", "192.168.182.0/24"]).unwrap();
+/// assert!(set.contains(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1))));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SubnetSet {
+    subnets: Vec<Subnet>,
+    trie: Trie,
+}
+
+impl SubnetSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        SubnetSet::default()
+    }
+
+    /// Create a set by parsing each of `subnets`.
+    pub fn from_strs(subnets: &[&str]) -> Result<Self, SubnetError> {
+        subnets.iter().map(|s| s.parse()).collect()
+    }
+
+    /// Add a subnet to the set.
+    pub fn insert(&mut self, subnet: Subnet) {
+        self.trie.insert(&subnet, self.subnets.len());
+        self.subnets.push(subnet);
+    }
+
+    /// The number of subnets in the set.
+    pub fn len(&self) -> usize {
+        self.subnets.len()
+    }
+
+    /// Check if the set has no subnets.
+    pub fn is_empty(&self) -> bool {
+        self.subnets.is_empty()
+    }
+
+    /// Check if any subnet in the set contains an address.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        self.trie.longest_match(addr).is_some()
+    }
+
+    /// The insertion index of the most specific subnet in the set that
+    /// contains `addr`. If the same subnet was inserted more than once, the
+    /// first insertion wins.
+    ///
+    /// # Examples
+    ///
+    /// ### Find the most specific subnet containing an address.
+    ///
+    /// ```
+    /// use std::net::{IpAddr, Ipv4Addr};
+    /// use subnet_utils::SubnetSet;
+    ///
+    /// let set = SubnetSet::from_strs(&["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24"]).unwrap();
+    /// assert_eq!(set.longest_match(&IpAddr::V4(Ipv4Addr::new(10, 1, 3, 1))), Some(1));
+    /// ```
+    pub fn longest_match(&self, addr: &IpAddr) -> Option<usize> {
+        self.trie.longest_match(addr)
+    }
+
+    /// Like [`SubnetSet::longest_match`] for each of `addrs`, in order.
+    ///
+    /// The addresses are walked through the trie in sorted order, so each walk
+    /// resumes from where it parts ways with the previous address's, and the
+    /// nodes on the prefix they share are only visited once.
+    pub fn longest_match_many(&self, addrs: &[IpAddr]) -> Vec<Option<usize>> {
+        self.trie.longest_match_many(addrs)
+    }
+
+    /// Iterate over the subnets in sorted order: IPv4 before IPv6, then by
+    /// network address and prefix length.
+    ///
+    /// The set doesn't aggregate, so subnets are yielded as inserted, including
+    /// any duplicates or overlaps.
+    pub fn iter(&self) -> impl Iterator<Item = &Subnet> {
+        let mut sorted: Vec<&Subnet> = self.subnets.iter().collect();
+        sorted.sort();
+        sorted.into_iter()
+    }
+
+    /// Encode the set in a compact binary format that [`SubnetSet::from_bytes`] can load.
+    ///
+    /// The layout is the magic bytes `SNET`, a version byte, the number of
+    /// subnets as a big-endian `u64`, then for each subnet a family tag (`4` or
+    /// `6`), the prefix length and the network address bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13 + self.subnets.len() * 18);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(self.subnets.len() as u64).to_be_bytes());
+        for subnet in self.subnets.iter() {
+            match subnet.network() {
+                IpAddr::V4(addr4) => {
+                    bytes.extend_from_slice(&[4, subnet.prefix_len()]);
+                    bytes.extend_from_slice(&addr4.octets());
+                }
+                IpAddr::V6(addr6) => {
+                    bytes.extend_from_slice(&[6, subnet.prefix_len()]);
+                    bytes.extend_from_slice(&addr6.octets());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Decode a set produced by [`SubnetSet::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<SubnetSet, SubnetError> {
+        let decode_error = |text: &str| SubnetError::DecodeError(text.to_string());
+        let (magic, rest) = bytes.split_at_checked(4).ok_or_else(|| decode_error("missing header"))?;
+        if magic != MAGIC {
+            return Err(decode_error("bad magic"));
+        }
+        let (&version, rest) = rest.split_first().ok_or_else(|| decode_error("missing version"))?;
+        if version != VERSION {
+            return Err(SubnetError::DecodeError(format!("unsupported version {}", version)));
+        }
+        let (count, mut rest) = rest.split_first_chunk::<8>().ok_or_else(|| decode_error("missing length"))?;
+        let count = u64::from_be_bytes(*count);
+
+        let mut set = SubnetSet::new();
+        for _ in 0..count {
+            let (&[family, prefix], tail) = rest.split_first_chunk::<2>().ok_or_else(|| decode_error("truncated entry"))?;
+            let v6 = match family {
+                4 => false,
+                6 => true,
+                _ => return Err(SubnetError::DecodeError(format!("unknown family tag {}", family))),
+            };
+            let len = if v6 { 16 } else { 4 };
+            let (network, tail) = tail.split_at_checked(len).ok_or_else(|| decode_error("truncated entry"))?;
+            let value = network.iter().fold(0u128, |acc, b| acc << 8 | *b as u128);
+            if prefix > max_prefix(v6) {
+                return Err(SubnetError::DecodeError(format!("invalid prefix length /{}", prefix)));
+            }
+            let subnet = Subnet::from_bits(value, prefix, v6);
+            if addr_to_bits(&subnet.network()) != value {
+                return Err(SubnetError::DecodeError(format!("host bits set in {}/{}", bits_to_addr(value, v6), prefix)));
+            }
+            set.insert(subnet);
+            rest = tail;
+        }
+        if !rest.is_empty() {
+            return Err(decode_error("trailing data"));
+        }
+        Ok(set)
+    }
+}
+
+impl FromIterator<Subnet> for SubnetSet {
+    fn from_iter<I: IntoIterator<Item = Subnet>>(iter: I) -> Self {
+        let mut set = SubnetSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Subnet> for SubnetSet {
+    fn extend<I: IntoIterator<Item = Subnet>>(&mut self, iter: I) {
+        for subnet in iter {
+            self.insert(subnet);
+        }
+    }
+}
+
+/// A binary trie over network bits, with separate IPv4 and IPv6 roots, mapping
+/// each subnet to the index it was inserted at.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Node {
+    children: [Option<usize>; 2],
+    index: Option<usize>,
+}
+
+impl Trie {
+    fn root(v6: bool) -> usize {
+        v6 as usize
+    }
+
+    fn insert(&mut self, subnet: &Subnet, index: usize) {
+        if self.nodes.is_empty() {
+            self.nodes = vec![Node::default(), Node::default()];
+        }
+        let (bits, width) = (subnet.first(), subnet.max_prefix());
+        let mut node = Trie::root(subnet.is_ipv6());
+        for depth in 0..subnet.prefix_len() {
+            let bit = (bits >> (width - 1 - depth) & 1) as usize;
+            node = match self.nodes[node].children[bit] {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children[bit] = Some(self.nodes.len() - 1);
+                    self.nodes.len() - 1
+                }
+            };
+        }
+        self.nodes[node].index.get_or_insert(index);
+    }
+
+    fn longest_match(&self, addr: &IpAddr) -> Option<usize> {
+        let mut node = self.nodes.get(Trie::root(addr.is_ipv6()))?;
+        let (bits, width) = (addr_to_bits(addr), max_prefix(addr.is_ipv6()));
+        let mut best = node.index;
+        for depth in 0..width {
+            let bit = (bits >> (width - 1 - depth) & 1) as usize;
+            match node.children[bit] {
+                Some(child) => node = &self.nodes[child],
+                None => break,
+            }
+            best = node.index.or(best);
+        }
+        best
+    }
+
+    fn longest_match_many(&self, addrs: &[IpAddr]) -> Vec<Option<usize>> {
+        let mut res = vec![None; addrs.len()];
+        if self.nodes.is_empty() {
+            return res;
+        }
+        let mut order: Vec<(bool, u128, usize)> =
+            addrs.iter().enumerate().map(|(i, addr)| (addr.is_ipv6(), addr_to_bits(addr), i)).collect();
+        order.sort_unstable();
+        // The node and best match at each depth of the previous address's walk.
+        let mut path: Vec<(usize, Option<usize>)> = Vec::new();
+        let mut prev: Option<(bool, u128)> = None;
+        for (v6, bits, i) in order {
+            let width = max_prefix(v6);
+            match prev {
+                Some((prev_v6, prev_bits)) if prev_v6 == v6 => {
+                    let shared = ((prev_bits ^ bits).leading_zeros() - (128 - width as u32)) as usize;
+                    path.truncate(shared.min(path.len() - 1) + 1);
+                }
+                _ => {
+                    let root = Trie::root(v6);
+                    path.clear();
+                    path.push((root, self.nodes[root].index));
+                }
+            }
+            let (mut node, mut best) = path[path.len() - 1];
+            for depth in path.len() as u8 - 1..width {
+                let bit = (bits >> (width - 1 - depth) & 1) as usize;
+                match self.nodes[node].children[bit] {
+                    Some(child) => node = child,
+                    None => break,
+                }
+                best = self.nodes[node].index.or(best);
+                path.push((node, best));
+            }
+            res[i] = best;
+            prev = Some((v6, bits));
+        }
+        res
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_set_contains() {
+        let set = SubnetSet::from_strs(&["192.168.181.0/24", "2001:db8::/32"]).unwrap();
+        assert!(set.contains(&"192.168.181.1".parse().unwrap()));
+        assert!(set.contains(&"2001:db8::1".parse().unwrap()));
+        assert!(!set.contains(&"192.168.182.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_subnet_set_bytes_round_trip() {
+        let set = SubnetSet::from_strs(&["192.168.181.0/24", "2001:db8::/32", "0.0.0.0/0", "::1/128"]).unwrap();
+        let bytes = set.to_bytes();
+        assert_eq!(&bytes[..5], b"SNET\x01");
+        assert_eq!(bytes.len(), 13 + 6 + 18 + 6 + 18);
+        assert_eq!(SubnetSet::from_bytes(&bytes).unwrap(), set);
+        assert_eq!(SubnetSet::from_bytes(&SubnetSet::new().to_bytes()).unwrap(), SubnetSet::new());
+    }
+
+    #[test]
+    fn test_subnet_set_from_invalid_bytes() {
+        let bytes = SubnetSet::from_strs(&["192.168.181.0/24"]).unwrap().to_bytes();
+        assert!(SubnetSet::from_bytes(b"").is_err());
+        assert!(SubnetSet::from_bytes(b"XNET\x01").is_err());
+        assert!(SubnetSet::from_bytes(&[b"SNET\x02", &bytes[5..]].concat()).is_err());
+        assert!(SubnetSet::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SubnetSet::from_bytes(&[&bytes[..], &[0]].concat()).is_err());
+        assert!(SubnetSet::from_bytes(&[&bytes[..bytes.len() - 1], &[1]].concat()).is_err());
+    }
+
+    #[test]
+    fn test_subnet_set_iter() {
+        let set = SubnetSet::from_strs(&["2001:db8::/32", "192.168.182.0/24", "10.0.0.0/16", "10.0.0.0/8"]).unwrap();
+        let res: Vec<String> = set.iter().map(|s| s.to_string()).collect();
+        assert_eq!(res, vec!["10.0.0.0/8", "10.0.0.0/16", "192.168.182.0/24", "2001:db8::/32"]);
+        assert_eq!(SubnetSet::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_subnet_set_longest_match() {
+        let set = SubnetSet::from_strs(&["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24", "10.1.0.0/16", "::/0", "2001:db8::/32", "10.1.2.3/32"]).unwrap();
+        assert_eq!(set.longest_match(&"10.1.2.1".parse().unwrap()), Some(2));
+        assert_eq!(set.longest_match(&"10.1.2.3".parse().unwrap()), Some(6));
+        assert_eq!(set.longest_match(&"10.1.3.1".parse().unwrap()), Some(1));
+        assert_eq!(set.longest_match(&"10.2.0.1".parse().unwrap()), Some(0));
+        assert_eq!(set.longest_match(&"11.0.0.1".parse().unwrap()), None);
+        assert_eq!(set.longest_match(&"2001:db8::1".parse().unwrap()), Some(5));
+        assert_eq!(set.longest_match(&"2001:db9::1".parse().unwrap()), Some(4));
+        assert_eq!(SubnetSet::new().longest_match(&"10.0.0.1".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn test_subnet_set_longest_match_many() {
+        let set = SubnetSet::from_strs(&["0.0.0.0/0", "192.168.182.0/24"]).unwrap();
+        let addrs = vec!["192.168.182.1".parse().unwrap(), "::1".parse().unwrap(), "10.0.0.1".parse().unwrap()];
+        assert_eq!(set.longest_match_many(&addrs), vec![Some(1), None, Some(0)]);
+        assert!(set.longest_match_many(&[]).is_empty());
+        assert_eq!(SubnetSet::new().longest_match_many(&addrs), vec![None; 3]);
+    }
+
+    #[test]
+    fn test_subnet_set_longest_match_many_shared_paths() {
+        let set = SubnetSet::from_strs(&["10.0.0.0/8", "10.1.0.0/16", "10.1.2.0/24", "10.1.2.3/32", "::/0", "2001:db8::/32"]).unwrap();
+        let addrs: Vec<IpAddr> = ["10.1.2.3", "2001:db8::1", "10.1.3.1", "10.1.2.3", "10.1.2.4", "11.0.0.1", "2001:db9::1", "10.2.0.1", "0.0.0.0"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let res: Vec<Option<usize>> = addrs.iter().map(|addr| set.longest_match(addr)).collect();
+        assert_eq!(set.longest_match_many(&addrs), res);
+    }
+}
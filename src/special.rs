@@ -0,0 +1,284 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::SubnetError;
+use crate::subnet::Subnet;
+
+
+/// IPv4 blocks that are not globally routable.
+const IPV4_NON_GLOBAL: &[(Ipv4Addr, u8)] = &[
+    (Ipv4Addr::new(0, 0, 0, 0), 8),
+    (Ipv4Addr::new(10, 0, 0, 0), 8),
+    (Ipv4Addr::new(100, 64, 0, 0), 10),
+    (Ipv4Addr::new(127, 0, 0, 0), 8),
+    (Ipv4Addr::new(169, 254, 0, 0), 16),
+    (Ipv4Addr::new(172, 16, 0, 0), 12),
+    (Ipv4Addr::new(192, 0, 0, 0), 24),
+    (Ipv4Addr::new(192, 0, 2, 0), 24),
+    (Ipv4Addr::new(192, 88, 99, 0), 24),
+    (Ipv4Addr::new(192, 168, 0, 0), 16),
+    (Ipv4Addr::new(198, 18, 0, 0), 15),
+    (Ipv4Addr::new(198, 51, 100, 0), 24),
+    (Ipv4Addr::new(203, 0, 113, 0), 24),
+    (Ipv4Addr::new(224, 0, 0, 0), 4),
+    (Ipv4Addr::new(240, 0, 0, 0), 4),
+];
+
+/// IPv6 global unicast space.
+const IPV6_GLOBAL_UNICAST: (Ipv6Addr, u8) = (Ipv6Addr::new(0x2000, 0, 0, 0, 0, 0, 0, 0), 3);
+
+/// Blocks within IPv6 global unicast space that are not globally routable.
+const IPV6_NON_GLOBAL: &[(Ipv6Addr, u8)] = &[
+    (Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0), 23),
+    (Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32),
+    (Ipv6Addr::new(0x2002, 0, 0, 0, 0, 0, 0, 0), 16),
+    (Ipv6Addr::new(0x3fff, 0, 0, 0, 0, 0, 0, 0), 20),
+];
+
+/// IANA IPv4 Special-Purpose Address Registry blocks.
+const IPV4_RESERVED: &[(Ipv4Addr, u8)] = &[
+    (Ipv4Addr::new(0, 0, 0, 0), 8),
+    (Ipv4Addr::new(10, 0, 0, 0), 8),
+    (Ipv4Addr::new(100, 64, 0, 0), 10),
+    (Ipv4Addr::new(127, 0, 0, 0), 8),
+    (Ipv4Addr::new(169, 254, 0, 0), 16),
+    (Ipv4Addr::new(172, 16, 0, 0), 12),
+    (Ipv4Addr::new(192, 0, 0, 0), 24),
+    (Ipv4Addr::new(192, 0, 2, 0), 24),
+    (Ipv4Addr::new(192, 31, 196, 0), 24),
+    (Ipv4Addr::new(192, 52, 193, 0), 24),
+    (Ipv4Addr::new(192, 88, 99, 0), 24),
+    (Ipv4Addr::new(192, 168, 0, 0), 16),
+    (Ipv4Addr::new(192, 175, 48, 0), 24),
+    (Ipv4Addr::new(198, 18, 0, 0), 15),
+    (Ipv4Addr::new(198, 51, 100, 0), 24),
+    (Ipv4Addr::new(203, 0, 113, 0), 24),
+    (Ipv4Addr::new(240, 0, 0, 0), 4),
+    (Ipv4Addr::new(255, 255, 255, 255), 32),
+];
+
+/// IANA IPv6 Special-Purpose Address Registry blocks.
+const IPV6_RESERVED: &[(Ipv6Addr, u8)] = &[
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 128),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 128),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0, 0), 96),
+    (Ipv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0, 0), 96),
+    (Ipv6Addr::new(0x64, 0xff9b, 1, 0, 0, 0, 0, 0), 48),
+    (Ipv6Addr::new(0x100, 0, 0, 0, 0, 0, 0, 0), 64),
+    (Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0), 23),
+    (Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32),
+    (Ipv6Addr::new(0x2002, 0, 0, 0, 0, 0, 0, 0), 16),
+    (Ipv6Addr::new(0x2620, 0x4f, 0x8000, 0, 0, 0, 0, 0), 48),
+    (Ipv6Addr::new(0x3fff, 0, 0, 0, 0, 0, 0, 0), 20),
+    (Ipv6Addr::new(0x5f00, 0, 0, 0, 0, 0, 0, 0), 16),
+    (Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7),
+    (Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), 10),
+];
+
+/// Documentation blocks from RFC 5737 and RFC 3849.
+const DOCUMENTATION: &[(IpAddr, u8)] = &[
+    (IpAddr::V4(Ipv4Addr::new(192, 0, 2, 0)), 24),
+    (IpAddr::V4(Ipv4Addr::new(198, 51, 100, 0)), 24),
+    (IpAddr::V4(Ipv4Addr::new(203, 0, 113, 0)), 24),
+    (IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0)), 32),
+];
+
+fn block(addr: impl Into<IpAddr>, prefix: u8) -> Subnet {
+    Subnet::from_parts(addr.into(), prefix).expect("valid special-purpose block")
+}
+
+fn overlaps(a: &Subnet, b: &Subnet) -> bool {
+    a.contains_subnet(b) || b.contains_subnet(a)
+}
+
+/// Checks that every address in `subnet` is globally routable unicast space.
+///
+/// IPv4 subnets must not overlap `0.0.0.0/8`, `10.0.0.0/8`, `100.64.0.0/10`,
+/// `127.0.0.0/8`, `169.254.0.0/16`, `172.16.0.0/12`, `192.0.0.0/24`,
+/// `192.0.2.0/24`, `192.88.99.0/24`, `192.168.0.0/16`, `198.18.0.0/15`,
+/// `198.51.100.0/24`, `203.0.113.0/24`, `224.0.0.0/4` or `240.0.0.0/4`.
+///
+/// IPv6 subnets must be within `2000::/3` and not overlap `2001::/23`,
+/// `2001:db8::/32`, `2002::/16` or `3fff::/20`.
+///
+/// # Examples
+///
+/// ### Check if a subnet is globally routable.
+///
+/// ```
+/// use subnet_utils::is_globally_routable;
+///
+/// assert!(is_globally_routable("8.8.8.0/24").unwrap());
+/// assert!(!is_globally_routable("10.0.0.0/24").unwrap());
+/// ```
+pub fn is_globally_routable(subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if subnet.is_ipv6() {
+        let (addr, prefix) = IPV6_GLOBAL_UNICAST;
+        return Ok(block(addr, prefix).contains_subnet(&subnet)
+            && !IPV6_NON_GLOBAL.iter().any(|&(addr, prefix)| overlaps(&block(addr, prefix), &subnet)));
+    }
+    Ok(!IPV4_NON_GLOBAL.iter().any(|&(addr, prefix)| overlaps(&block(addr, prefix), &subnet)))
+}
+
+/// Checks if `addr` is in a block of the IANA special-purpose address
+/// registries, which covers private, loopback and documentation space as well
+/// as other ranges that should not appear as real hosts.
+///
+/// The IPv4 blocks are `0.0.0.0/8`, `10.0.0.0/8`, `100.64.0.0/10`,
+/// `127.0.0.0/8`, `169.254.0.0/16`, `172.16.0.0/12`, `192.0.0.0/24`,
+/// `192.0.2.0/24`, `192.31.196.0/24`, `192.52.193.0/24`, `192.88.99.0/24`,
+/// `192.168.0.0/16`, `192.175.48.0/24`, `198.18.0.0/15`, `198.51.100.0/24`,
+/// `203.0.113.0/24`, `240.0.0.0/4` and `255.255.255.255/32`.
+///
+/// The IPv6 blocks are `::/128`, `::1/128`, `::ffff:0:0/96`, `64:ff9b::/96`,
+/// `64:ff9b:1::/48`, `100::/64`, `2001::/23`, `2001:db8::/32`, `2002::/16`,
+/// `2620:4f:8000::/48`, `3fff::/20`, `5f00::/16`, `fc00::/7` and `fe80::/10`.
+///
+/// Multicast addresses are not included.
+///
+/// # Examples
+///
+/// ### Check if an address is reserved.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::is_reserved;
+///
+/// assert!(is_reserved(&IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+/// assert!(!is_reserved(&IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8))));
+/// ```
+pub fn is_reserved(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(_) => IPV4_RESERVED.iter().any(|&(net, prefix)| block(net, prefix).contains(addr)),
+        IpAddr::V6(_) => IPV6_RESERVED.iter().any(|&(net, prefix)| block(net, prefix).contains(addr)),
+    }
+}
+
+
+/// Checks that `subnet` lies entirely within one of the documentation ranges,
+/// `192.0.2.0/24`, `198.51.100.0/24` and `203.0.113.0/24` ([RFC
+/// 5737](https://www.rfc-editor.org/rfc/rfc5737)) or `2001:db8::/32` ([RFC
+/// 3849](https://www.rfc-editor.org/rfc/rfc3849)).
+///
+/// # Examples
+///
+/// ### Check if a subnet is reserved for documentation.
+///
+/// ```
+/// use subnet_utils::is_documentation_subnet;
+///
+/// assert!(is_documentation_subnet("198.51.100.128/25").unwrap());
+/// assert!(!is_documentation_subnet("192.168.182.0/24").unwrap());
+/// ```
+pub fn is_documentation_subnet(subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    Ok(DOCUMENTATION.iter().any(|&(net, prefix)| block(net, prefix).contains_subnet(&subnet)))
+}
+
+
+/// Checks if an IPv4 `subnet` spans more than one classful network. IPv6
+/// subnets always return `false`.
+///
+/// The classful networks are the `/8`s of class A (`0.0.0.0` to
+/// `127.255.255.255`), the `/16`s of class B (`128.0.0.0` to
+/// `191.255.255.255`) and the `/24`s of class C (`192.0.0.0` to
+/// `223.255.255.255`). Class D (`224.0.0.0/4`) and class E (`240.0.0.0/4`)
+/// are each treated as a single network.
+///
+/// # Examples
+///
+/// ### Check if a supernet crosses classful boundaries.
+///
+/// ```
+/// use subnet_utils::crosses_classful_boundary;
+///
+/// assert!(crosses_classful_boundary("192.168.0.0/16").unwrap());
+/// assert!(!crosses_classful_boundary("10.1.0.0/16").unwrap());
+/// ```
+pub fn crosses_classful_boundary(subnet: &str) -> Result<bool, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if subnet.is_ipv6() {
+        return Ok(false);
+    }
+    Ok(classful_network(subnet.first() as u32) != classful_network(subnet.last() as u32))
+}
+
+/// The classful network containing an IPv4 address, as a masked integer.
+fn classful_network(addr: u32) -> u32 {
+    match addr >> 28 {
+        0..=7 => addr & 0xff00_0000,
+        8..=11 => addr & 0xffff_0000,
+        12..=13 => addr & 0xffff_ff00,
+        _ => addr & 0xf000_0000,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_globally_routable() {
+        assert!(is_globally_routable("8.8.8.0/24").unwrap());
+        assert!(is_globally_routable("11.0.0.0/8").unwrap());
+        assert!(is_globally_routable("2606:4700::/32").unwrap());
+    }
+
+    #[test]
+    fn test_is_not_globally_routable() {
+        assert!(!is_globally_routable("192.168.182.0/24").unwrap());
+        assert!(!is_globally_routable("100.64.0.0/16").unwrap());
+        assert!(!is_globally_routable("224.0.0.1/32").unwrap());
+        assert!(!is_globally_routable("8.0.0.0/4").unwrap());
+        assert!(!is_globally_routable("2001:db8::/48").unwrap());
+        assert!(!is_globally_routable("fe80::/64").unwrap());
+        assert!(!is_globally_routable("::/0").unwrap());
+    }
+
+    #[test]
+    fn test_is_reserved() {
+        for addr in ["0.1.2.3", "10.0.0.1", "192.0.2.1", "198.51.100.7", "240.0.0.1", "255.255.255.255", "::", "::1", "2001:db8::1", "fe80::1"] {
+            assert!(is_reserved(&addr.parse().unwrap()), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn test_is_not_reserved() {
+        for addr in ["8.8.8.8", "1.1.1.1", "224.0.0.1", "2606:4700::1111", "ff02::1"] {
+            assert!(!is_reserved(&addr.parse().unwrap()), "{}", addr);
+        }
+    }
+
+    #[test]
+    fn test_is_documentation_subnet() {
+        for subnet in ["192.0.2.0/24", "198.51.100.7/32", "203.0.113.64/26", "2001:db8::/32", "2001:db8:1::/48"] {
+            assert!(is_documentation_subnet(subnet).unwrap(), "{}", subnet);
+        }
+    }
+
+    #[test]
+    fn test_is_not_documentation_subnet() {
+        for subnet in ["192.0.2.0/23", "10.0.0.0/8", "2001:db8::/31", "2001:db9::/32", "::/0"] {
+            assert!(!is_documentation_subnet(subnet).unwrap(), "{}", subnet);
+        }
+    }
+
+    #[test]
+    fn test_crosses_classful_boundary() {
+        assert!(crosses_classful_boundary("10.0.0.0/7").unwrap());
+        assert!(crosses_classful_boundary("172.16.0.0/12").unwrap());
+        assert!(crosses_classful_boundary("192.168.0.0/23").unwrap());
+        assert!(crosses_classful_boundary("0.0.0.0/0").unwrap());
+        assert!(crosses_classful_boundary("208.0.0.0/3").unwrap());
+    }
+
+    #[test]
+    fn test_not_crosses_classful_boundary() {
+        assert!(!crosses_classful_boundary("10.0.0.0/8").unwrap());
+        assert!(!crosses_classful_boundary("172.16.0.0/16").unwrap());
+        assert!(!crosses_classful_boundary("192.168.182.128/25").unwrap());
+        assert!(!crosses_classful_boundary("224.0.0.0/4").unwrap());
+        assert!(!crosses_classful_boundary("::/0").unwrap());
+    }
+}
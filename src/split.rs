@@ -0,0 +1,228 @@
+use crate::SubnetError;
+use crate::subnet::{Subnet, check_prefix};
+
+
+/// The maximum number of blocks [`to_standard_blocks`] and the other
+/// splitting functions in this module will return.
+pub const STANDARD_BLOCKS_LIMIT: u128 = 65_536;
+
+/// Splits an IPv4 subnet into `/24`s or an IPv6 subnet into `/64`s. Subnets
+/// that are already that size or smaller are returned unchanged.
+///
+/// Returns [`SubnetError::TooLarge`] rather than expanding into more than
+/// [`STANDARD_BLOCKS_LIMIT`] blocks, so an IPv4 `/8` is the largest subnet
+/// accepted for IPv4 and an IPv6 `/48` for IPv6.
+///
+/// # Examples
+///
+/// ### Split a subnet into /24s.
+///
+/// ```
+/// use subnet_utils::to_standard_blocks;
+///
+/// let res = to_standard_blocks("192.168.182.0/23").unwrap();
+/// assert_eq!(res, vec!["192.168.182.0/24", "192.168.183.0/24"]);
+/// ```
+pub fn to_standard_blocks(subnet: &str) -> Result<Vec<String>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    let standard = if subnet.is_ipv6() { 64 } else { 24 };
+    if subnet.prefix_len() >= standard {
+        return Ok(vec![subnet.to_string()]);
+    }
+    let count = 1u128 << (standard - subnet.prefix_len());
+    if count > STANDARD_BLOCKS_LIMIT {
+        return Err(SubnetError::TooLarge { size: count, limit: STANDARD_BLOCKS_LIMIT });
+    }
+    Ok(subnet.subnets(standard).map(|s| s.to_string()).collect())
+}
+
+
+/// Splits `subnet` into `n` equal blocks, in address order.
+///
+/// CIDR blocks can only be halved, so `n` must be a power of two, and the
+/// blocks must be no smaller than a single address. Returns
+/// [`SubnetError::TooLarge`] if `n` is more than [`STANDARD_BLOCKS_LIMIT`].
+///
+/// # Examples
+///
+/// ### Divide a subnet into four blocks.
+///
+/// ```
+/// use subnet_utils::divide_into;
+///
+/// let res = divide_into("10.0.0.0/24", 4).unwrap();
+/// assert_eq!(res, vec!["10.0.0.0/26", "10.0.0.64/26", "10.0.0.128/26", "10.0.0.192/26"]);
+/// ```
+pub fn divide_into(subnet: &str, n: u32) -> Result<Vec<String>, SubnetError> {
+    let subnet: Subnet = subnet.parse()?;
+    if !n.is_power_of_two() {
+        return Err(SubnetError::InvalidArgument(format!(
+            "cannot divide into {} equal blocks, only a power of two is possible",
+            n
+        )));
+    }
+    let prefix = subnet.prefix_len() + n.trailing_zeros() as u8;
+    if prefix > subnet.max_prefix() {
+        return Err(SubnetError::InvalidArgument(format!("{} has fewer than {} addresses", subnet, n)));
+    }
+    if n as u128 > STANDARD_BLOCKS_LIMIT {
+        return Err(SubnetError::TooLarge { size: n as u128, limit: STANDARD_BLOCKS_LIMIT });
+    }
+    Ok(subnet.subnets(prefix).map(|s| s.to_string()).collect())
+}
+
+
+/// The `count` blocks of length `child_prefix` within `parent`, starting at
+/// the `start`-th such block, in address order.
+///
+/// Returns [`SubnetError::InvalidArgument`] if `child_prefix` is shorter than
+/// the parent's prefix or the requested blocks run past the end of `parent`,
+/// and [`SubnetError::TooLarge`] if `count` is more than
+/// [`STANDARD_BLOCKS_LIMIT`].
+///
+/// # Examples
+///
+/// ### Get the third and fourth /26 of a subnet.
+///
+/// ```
+/// use subnet_utils::child_subnets_range;
+///
+/// let res = child_subnets_range("10.0.0.0/24", 26, 2, 2).unwrap();
+/// assert_eq!(res, vec!["10.0.0.128/26", "10.0.0.192/26"]);
+/// ```
+pub fn child_subnets_range(parent: &str, child_prefix: u8, start: u128, count: u128) -> Result<Vec<String>, SubnetError> {
+    let parent: Subnet = parent.parse()?;
+    let v6 = parent.is_ipv6();
+    check_prefix(child_prefix, v6)?;
+    if child_prefix < parent.prefix_len() {
+        return Err(SubnetError::InvalidArgument(format!("/{} is shorter than {}", child_prefix, parent)));
+    }
+    // `None` is 2^128, the number of /128s in ::/0.
+    let children = 1u128.checked_shl((child_prefix - parent.prefix_len()) as u32);
+    let end = start.checked_add(count);
+    if end.is_none() || children.is_some_and(|children| end > Some(children)) {
+        return Err(SubnetError::InvalidArgument(format!(
+            "{} /{} blocks from index {} run past the end of {}",
+            count, child_prefix, start, parent
+        )));
+    }
+    if count > STANDARD_BLOCKS_LIMIT {
+        return Err(SubnetError::TooLarge { size: count, limit: STANDARD_BLOCKS_LIMIT });
+    }
+    let shift = (parent.max_prefix() - child_prefix) as u32;
+    Ok((start..start + count)
+        .map(|index| Subnet::from_bits(parent.first() | index.checked_shl(shift).unwrap_or(0), child_prefix, v6).to_string())
+        .collect())
+}
+
+
+/// The `index`-th block, counting from zero, that is `depth` prefix lengths
+/// below `parent`, e.g. the /24s of a /16 at depth 8. Errors as
+/// [`child_subnets_range`] does if the prefix is too long or `index` is out of
+/// range.
+///
+/// # Examples
+///
+/// ### Get the /24 at index 5 of a /16.
+///
+/// ```
+/// use subnet_utils::subnet_at_depth;
+///
+/// let res = subnet_at_depth("10.0.0.0/16", 8, 5).unwrap();
+/// assert_eq!(res, "10.0.5.0/24");
+/// ```
+pub fn subnet_at_depth(parent: &str, depth: u8, index: u128) -> Result<String, SubnetError> {
+    let prefix = parent.parse::<Subnet>()?.prefix_len().saturating_add(depth);
+    let mut res = child_subnets_range(parent, prefix, index, 1)?;
+    Ok(res.remove(0))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_standard_blocks() {
+        let res = to_standard_blocks("2001:db8::/63").unwrap();
+        assert_eq!(res, vec!["2001:db8::/64", "2001:db8:0:1::/64"]);
+        let res = to_standard_blocks("10.0.0.0/8").unwrap();
+        assert_eq!(res.len(), 65_536);
+        assert_eq!(res.last().unwrap(), "10.255.255.0/24");
+        let res = to_standard_blocks("192.168.182.128/25").unwrap();
+        assert_eq!(res, vec!["192.168.182.128/25"]);
+    }
+
+    #[test]
+    fn test_to_standard_blocks_too_large() {
+        let res = to_standard_blocks("10.0.0.0/7");
+        assert_eq!(res, Err(SubnetError::TooLarge { size: 131_072, limit: STANDARD_BLOCKS_LIMIT }));
+        assert!(to_standard_blocks("2001:db8::/32").is_err());
+    }
+
+    #[test]
+    fn test_divide_into() {
+        assert_eq!(divide_into("10.0.0.0/24", 1).unwrap(), vec!["10.0.0.0/24"]);
+        assert_eq!(divide_into("10.0.0.0/31", 2).unwrap(), vec!["10.0.0.0/32", "10.0.0.1/32"]);
+        let res = divide_into("2001:db8::/32", 256).unwrap();
+        assert_eq!(res.len(), 256);
+        assert_eq!(res[255], "2001:db8:ff00::/40");
+    }
+
+    #[test]
+    fn test_divide_into_invalid() {
+        assert!(matches!(divide_into("10.0.0.0/24", 3), Err(SubnetError::InvalidArgument(_))));
+        assert!(matches!(divide_into("10.0.0.0/24", 0), Err(SubnetError::InvalidArgument(_))));
+        assert!(matches!(divide_into("10.0.0.0/30", 8), Err(SubnetError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_divide_into_too_large() {
+        let res = divide_into("::/0", 1 << 31);
+        assert_eq!(res, Err(SubnetError::TooLarge { size: 1 << 31, limit: STANDARD_BLOCKS_LIMIT }));
+        assert_eq!(divide_into("::/0", 1 << 16).unwrap().len(), 65_536);
+        assert!(divide_into("::/0", 1 << 17).is_err());
+    }
+
+    #[test]
+    fn test_child_subnets_range() {
+        let res = child_subnets_range("10.0.0.0/16", 26, 32, 3).unwrap();
+        assert_eq!(res, vec!["10.0.8.0/26", "10.0.8.64/26", "10.0.8.128/26"]);
+        let res = child_subnets_range("10.0.0.0/24", 24, 0, 1).unwrap();
+        assert_eq!(res, vec!["10.0.0.0/24"]);
+        let res = child_subnets_range("::/0", 128, u128::MAX - 1, 1).unwrap();
+        assert_eq!(res, vec!["ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe/128"]);
+        assert!(child_subnets_range("::/0", 0, 0, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_child_subnets_range_invalid() {
+        assert!(matches!(child_subnets_range("10.0.0.0/24", 26, 3, 2), Err(SubnetError::InvalidArgument(_))));
+        assert!(matches!(child_subnets_range("10.0.0.0/24", 23, 0, 1), Err(SubnetError::InvalidArgument(_))));
+        assert!(matches!(child_subnets_range("::/0", 128, u128::MAX, 2), Err(SubnetError::InvalidArgument(_))));
+        assert!(child_subnets_range("10.0.0.0/24", 33, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_child_subnets_range_too_large() {
+        let res = child_subnets_range("::/0", 128, 0, 1 << 70);
+        assert_eq!(res, Err(SubnetError::TooLarge { size: 1 << 70, limit: STANDARD_BLOCKS_LIMIT }));
+        assert_eq!(child_subnets_range("10.0.0.0/8", 32, 0, STANDARD_BLOCKS_LIMIT).unwrap().len(), 65_536);
+        assert!(child_subnets_range("10.0.0.0/8", 32, 0, STANDARD_BLOCKS_LIMIT + 1).is_err());
+    }
+
+    #[test]
+    fn test_subnet_at_depth() {
+        assert_eq!(subnet_at_depth("10.0.0.0/16", 8, 255).unwrap(), "10.0.255.0/24");
+        assert_eq!(subnet_at_depth("10.0.0.0/16", 0, 0).unwrap(), "10.0.0.0/16");
+        assert_eq!(subnet_at_depth("10.0.0.0/16", 2, 3).unwrap(), "10.0.192.0/18");
+        assert_eq!(subnet_at_depth("2001:db8::/32", 16, 1).unwrap(), "2001:db8:1::/48");
+    }
+
+    #[test]
+    fn test_subnet_at_depth_invalid() {
+        assert!(matches!(subnet_at_depth("10.0.0.0/16", 8, 256), Err(SubnetError::InvalidArgument(_))));
+        assert!(matches!(subnet_at_depth("10.0.0.0/16", 17, 0), Err(SubnetError::InvalidPrefix { .. })));
+        assert!(matches!(subnet_at_depth("2001:db8::/32", 255, 0), Err(SubnetError::InvalidPrefix { .. })));
+    }
+}
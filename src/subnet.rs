@@ -0,0 +1,383 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::SubnetError;
+use crate::parse::{normalize, parse_netaddr};
+
+
+/// An IP address family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Family {
+    /// IPv4.
+    V4,
+    /// IPv6.
+    V6,
+}
+
+/// A CIDR subnet, stored as its network address and prefix length.
+///
+/// Host bits are zeroed when parsing, and subnets order by family, network
+/// address and then prefix length.
+///
+/// # Examples
+///
+/// ### Parse a subnet and check if it contains an address.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use subnet_utils::Subnet;
+///
+/// let subnet: Subnet = "192.168.182.1/24".parse().unwrap();
+/// assert_eq!(subnet.to_string(), "192.168.182.0/24");
+/// assert!(subnet.contains(&IpAddr::V4(Ipv4Addr::new(192, 168, 182, 1))));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Subnet {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Subnet {
+    /// Build a subnet from an address and prefix length, zeroing the host bits.
+    pub fn from_parts(addr: IpAddr, prefix: u8) -> Result<Subnet, SubnetError> {
+        check_prefix(prefix, addr.is_ipv6())?;
+        Ok(Subnet::from_bits(addr_to_bits(&addr), prefix, addr.is_ipv6()))
+    }
+
+    /// Build a subnet from an integer address, zeroing the host bits.
+    pub(crate) fn from_bits(value: u128, prefix: u8, v6: bool) -> Subnet {
+        Subnet {
+            network: bits_to_addr(value & mask(prefix, v6), v6),
+            prefix,
+        }
+    }
+
+    /// The network address of the subnet.
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    /// The prefix length of the subnet.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Check if the subnet is an IPv4 subnet.
+    pub fn is_ipv4(&self) -> bool {
+        self.network.is_ipv4()
+    }
+
+    /// Check if the subnet is an IPv6 subnet.
+    pub fn is_ipv6(&self) -> bool {
+        self.network.is_ipv6()
+    }
+
+    /// The address family of the subnet.
+    pub fn family(&self) -> Family {
+        if self.is_ipv6() { Family::V6 } else { Family::V4 }
+    }
+
+    /// Check if `addr` is the same family as the subnet, without checking containment.
+    pub fn family_matches(&self, addr: &IpAddr) -> bool {
+        addr.is_ipv6() == self.is_ipv6()
+    }
+
+    /// The maximum prefix length of the subnet's family.
+    pub(crate) fn max_prefix(&self) -> u8 {
+        max_prefix(self.is_ipv6())
+    }
+
+    /// The network address as an integer.
+    pub(crate) fn first(&self) -> u128 {
+        addr_to_bits(&self.network)
+    }
+
+    /// The last address of the subnet as an integer.
+    pub(crate) fn last(&self) -> u128 {
+        self.first() | host_mask(self.prefix, self.is_ipv6())
+    }
+
+    /// The first and last usable host addresses as integers. IPv4 excludes the
+    /// network and broadcast addresses and IPv6 the subnet-router anycast
+    /// address, except in /31, /32, /127 and /128 subnets where every address
+    /// is usable.
+    pub(crate) fn usable(&self) -> (u128, u128) {
+        if self.max_prefix() - self.prefix <= 1 {
+            return (self.first(), self.last());
+        }
+        let last = if self.is_ipv6() { self.last() } else { self.last() - 1 };
+        (self.first() + 1, last)
+    }
+
+    /// The number of addresses in the subnet, saturating at `u128::MAX` for `::/0`.
+    pub(crate) fn size(&self) -> u128 {
+        1u128.checked_shl((self.max_prefix() - self.prefix) as u32).unwrap_or(u128::MAX)
+    }
+
+    /// Iterate over the subnets of length `prefix` within this subnet, which
+    /// must be at least as long as the subnet's own prefix.
+    pub(crate) fn subnets(&self, prefix: u8) -> Subnets {
+        Subnets {
+            next: Some(self.first()),
+            last: self.last(),
+            prefix,
+            v6: self.is_ipv6(),
+        }
+    }
+
+    /// Check if the subnet contains an address. Addresses of the other family never match.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        self.family_matches(addr) && addr_to_bits(addr) & mask(self.prefix, self.is_ipv6()) == self.first()
+    }
+
+    /// Check if the subnet fully contains another subnet, including when they are equal.
+    pub fn contains_subnet(&self, other: &Subnet) -> bool {
+        other.prefix >= self.prefix && self.contains(&other.network)
+    }
+}
+
+impl FromStr for Subnet {
+    type Err = SubnetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = normalize(s);
+        let net = parse_netaddr(&s)?;
+        let v6 = net.addr().is_ipv6();
+        if let Some((_, prefix)) = s.split_once('/')
+            && prefix.bytes().all(|b| b.is_ascii_digit())
+            && prefix.parse::<u8>().map_or(true, |p| p > max_prefix(v6))
+        {
+            return Err(SubnetError::ParseError(format!("invalid prefix length in {}", s)));
+        }
+        let prefix = addr_to_bits(&net.mask()).count_ones() as u8;
+        Ok(Subnet { network: net.addr(), prefix })
+    }
+}
+
+impl fmt::Display for Subnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// Iterator over equally sized subnets of a parent, see [`Subnet::subnets`].
+#[derive(Clone, Debug)]
+pub(crate) struct Subnets {
+    next: Option<u128>,
+    last: u128,
+    prefix: u8,
+    v6: bool,
+}
+
+impl Subnets {
+    /// The distance between consecutive subnets, or `None` if the prefix is
+    /// `0` and there's only one.
+    fn step(&self) -> Option<u128> {
+        host_mask(self.prefix, self.v6).checked_add(1)
+    }
+
+    /// The number of subnets left to yield, saturating at `u128::MAX` for all
+    /// the IPv6 addresses.
+    fn remaining(&self) -> u128 {
+        match (self.next, self.step()) {
+            (None, _) => 0,
+            (Some(_), None) => 1,
+            (Some(next), Some(step)) => ((self.last - next) / step).saturating_add(1),
+        }
+    }
+}
+
+impl Iterator for Subnets {
+    type Item = Subnet;
+
+    fn next(&mut self) -> Option<Subnet> {
+        let current = self.next?;
+        self.next = self.step().and_then(|step| current.checked_add(step)).filter(|next| *next <= self.last);
+        Some(Subnet::from_bits(current, self.prefix, self.v6))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Subnet> {
+        if n as u128 >= self.remaining() {
+            self.next = None;
+            return None;
+        }
+        if let (Some(next), Some(step)) = (self.next, self.step()) {
+            self.next = Some(next + n as u128 * step);
+        }
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        usize::try_from(remaining).map_or((usize::MAX, None), |n| (n, Some(n)))
+    }
+}
+
+/// A subnet string that is parsed on first use and cached afterwards, for use
+/// with [`addr_in_subnet_cached`](crate::addr_in_subnet_cached).
+///
+/// Once parsed, checks against it don't allocate. Callers that can parse up
+/// front get the same by parsing a [`Subnet`] once and calling [`Subnet::contains`].
+#[derive(Clone, Debug)]
+pub struct ParsedSubnet<'a> {
+    source: &'a str,
+    parsed: Option<Result<Subnet, SubnetError>>,
+}
+
+impl<'a> ParsedSubnet<'a> {
+    /// Wrap a subnet string without parsing it yet.
+    pub fn new(source: &'a str) -> Self {
+        ParsedSubnet { source, parsed: None }
+    }
+
+    /// The subnet, parsing it if this is the first use. A parse error is
+    /// borrowed from the cache rather than cloned, so it isn't rebuilt on every call.
+    pub fn subnet(&mut self) -> Result<Subnet, &SubnetError> {
+        self.parsed.get_or_insert_with(|| self.source.parse()).as_ref().copied()
+    }
+}
+
+
+/// The maximum prefix length for IPv6 (`128`) or IPv4 (`32`).
+pub(crate) fn max_prefix(v6: bool) -> u8 {
+    if v6 { 128 } else { 32 }
+}
+
+/// Check that `prefix` is a valid prefix length for the family.
+pub(crate) fn check_prefix(prefix: u8, v6: bool) -> Result<(), SubnetError> {
+    let max = max_prefix(v6);
+    if prefix > max {
+        return Err(SubnetError::InvalidPrefix { prefix, max });
+    }
+    Ok(())
+}
+
+pub(crate) fn addr_to_bits(addr: &IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(addr4) => u32::from(*addr4) as u128,
+        IpAddr::V6(addr6) => u128::from(*addr6),
+    }
+}
+
+pub(crate) fn bits_to_addr(value: u128, v6: bool) -> IpAddr {
+    if v6 {
+        IpAddr::V6(Ipv6Addr::from(value))
+    } else {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    }
+}
+
+/// The network mask for `prefix` as an integer within the family's width.
+pub(crate) fn mask(prefix: u8, v6: bool) -> u128 {
+    !host_mask(prefix, v6) & host_mask(0, v6)
+}
+
+/// The host mask for `prefix` as an integer within the family's width.
+pub(crate) fn host_mask(prefix: u8, v6: bool) -> u128 {
+    u128::MAX.checked_shr((128 - max_prefix(v6) + prefix) as u32).unwrap_or(0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subnet_parse() {
+        let subnet: Subnet = "192.168.182.1/24".parse().unwrap();
+        assert_eq!(subnet.to_string(), "192.168.182.0/24");
+        assert_eq!(subnet.size(), 256);
+        let subnet: Subnet = "2001:db8::/32".parse().unwrap();
+        assert_eq!(subnet.to_string(), "2001:db8::/32");
+        let subnet: Subnet = "::/0".parse().unwrap();
+        assert_eq!(subnet.size(), u128::MAX);
+    }
+
+    #[test]
+    fn test_subnet_parse_invalid() {
+        assert!("192.168.182.0/33".parse::<Subnet>().is_err());
+        assert!("192.168.182.0/255.0.255.0".parse::<Subnet>().is_err());
+        assert!("zoop".parse::<Subnet>().is_err());
+    }
+
+    #[test]
+    fn test_subnet_parse_non_contiguous_mask() {
+        for mask in ["255.255.0.255", "255.0.255.0", "0.255.255.255", "255.255.255.253"] {
+            let res = format!("10.0.0.0/{}", mask).parse::<Subnet>();
+            assert_eq!(res, Err(SubnetError::NonContiguousMask { mask: mask.to_string() }));
+        }
+        let res = "10.0.0.0 255.255.0.255".parse::<Subnet>();
+        assert_eq!(res, Err(SubnetError::NonContiguousMask { mask: "255.255.0.255".to_string() }));
+    }
+
+    #[test]
+    fn test_subnet_parse_messy_input() {
+        let subnet: Subnet = " 2001:DB8::/32 ".parse().unwrap();
+        assert_eq!(subnet.to_string(), "2001:db8::/32");
+        let subnet: Subnet = "\t192.168.182.0/24 ".parse().unwrap();
+        assert_eq!(subnet.to_string(), "192.168.182.0/24");
+    }
+
+    #[test]
+    fn test_parsed_subnet() {
+        let mut parsed = ParsedSubnet::new("192.168.182.1/24");
+        assert_eq!(parsed.subnet().unwrap().to_string(), "192.168.182.0/24");
+        assert_eq!(parsed.subnet().unwrap().to_string(), "192.168.182.0/24");
+        let mut parsed = ParsedSubnet::new("zoop");
+        let err: *const SubnetError = parsed.subnet().unwrap_err();
+        assert!(std::ptr::eq(err, parsed.subnet().unwrap_err()));
+        assert!(matches!(parsed.subnet(), Err(SubnetError::ParseError(_))));
+    }
+
+    #[test]
+    fn test_subnet_from_parts() {
+        let subnet = Subnet::from_parts("10.0.0.5".parse().unwrap(), 24).unwrap();
+        assert_eq!(subnet, "10.0.0.0/24".parse().unwrap());
+        let subnet = Subnet::from_parts("2001:db8::1".parse().unwrap(), 128).unwrap();
+        assert_eq!(subnet, "2001:db8::1/128".parse().unwrap());
+        let res = Subnet::from_parts("10.0.0.5".parse().unwrap(), 33);
+        assert_eq!(res, Err(SubnetError::InvalidPrefix { prefix: 33, max: 32 }));
+    }
+
+    #[test]
+    fn test_subnets_nth() {
+        let subnet: Subnet = "10.0.0.0/16".parse().unwrap();
+        let mut subnets = subnet.subnets(24);
+        assert_eq!(subnets.nth(2), Some("10.0.2.0/24".parse().unwrap()));
+        assert_eq!(subnets.next(), Some("10.0.3.0/24".parse().unwrap()));
+        assert_eq!(subnets.nth(251), Some("10.0.255.0/24".parse().unwrap()));
+        assert_eq!(subnets.next(), None);
+        assert_eq!(subnet.subnets(24).nth(256), None);
+        assert_eq!(subnet.subnets(24).nth(usize::MAX), None);
+        let all: Subnet = "::/0".parse().unwrap();
+        assert_eq!(all.subnets(0).next(), Some(all));
+        assert_eq!(all.subnets(0).nth(1), None);
+        assert_eq!(all.subnets(128).nth(usize::MAX), Some(Subnet::from_bits(usize::MAX as u128, 128, true)));
+    }
+
+    #[test]
+    fn test_subnets_size_hint() {
+        let subnet: Subnet = "10.0.0.0/16".parse().unwrap();
+        let mut subnets = subnet.subnets(24);
+        assert_eq!(subnets.size_hint(), (256, Some(256)));
+        subnets.nth(9);
+        assert_eq!(subnets.size_hint(), (246, Some(246)));
+        assert_eq!(subnets.count(), 246);
+        assert_eq!(subnet.subnets(16).size_hint(), (1, Some(1)));
+        let all: Subnet = "::/0".parse().unwrap();
+        assert_eq!(all.subnets(0).size_hint(), (1, Some(1)));
+        assert_eq!(all.subnets(128).size_hint(), (usize::MAX, None));
+    }
+
+    #[test]
+    fn test_subnet_family_matches() {
+        let subnet: Subnet = "10.0.0.0/8".parse().unwrap();
+        assert!(subnet.family_matches(&"192.168.0.1".parse().unwrap()));
+        assert!(!subnet.family_matches(&"::ffff:10.0.0.1".parse().unwrap()));
+        let subnet: Subnet = "::/0".parse().unwrap();
+        assert!(subnet.family_matches(&"2001:db8::1".parse().unwrap()));
+        assert!(!subnet.family_matches(&"0.0.0.0".parse().unwrap()));
+    }
+}